@@ -0,0 +1,3 @@
+pub mod maze;
+pub mod three_d;
+pub mod two_d;
@@ -1,10 +1,35 @@
-mod maze;
-mod three_d;
+use anyhow::{Result, bail};
+use clap::builder::RangedU64ValueParser;
+use clap::{Parser, ValueEnum};
+use maze_maker::maze::{CylinderMaze, DisplayStyle, VerticalBias};
+use maze_maker::three_d::{
+    CylinderConfig, MazeSurface, check_fit, make_outer_openscad, maze_to_openscad,
+    printability_report, solution_to_openscad,
+};
+use maze_maker::two_d::{export_flat_svg, export_wrap_svg};
 
-use anyhow::Result;
-use clap::Parser;
-use maze::CylinderMaze;
-use three_d::{make_outer_openscad, maze_to_openscad};
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Algorithm {
+    /// Wilson's loop-erased random walk
+    Wilson,
+    /// Recursive division into chambers
+    RecursiveDivision,
+    /// Wilson's algorithm, mirrored so the maze is the same from either end
+    Symmetric,
+    /// A helical solution winding around the cylinder, with dead ends off it
+    Spiral,
+}
+
+impl Algorithm {
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Wilson => "Wilson's Algorithm",
+            Algorithm::RecursiveDivision => "Recursive Division",
+            Algorithm::Symmetric => "Symmetric",
+            Algorithm::Spiral => "Spiral",
+        }
+    }
+}
 
 #[derive(Parser, Debug)]
 #[command(name = "maze_maker")]
@@ -18,6 +43,37 @@ struct Args {
     #[arg(short, long, default_value_t = 20)]
     cols: usize,
 
+    /// Seed for the random numbers, to make the same maze again. Picked at random if not
+    /// given, and printed either way.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Algorithm used to generate the maze
+    #[arg(long, value_enum, default_value_t = Algorithm::Wilson)]
+    algorithm: Algorithm,
+
+    /// How much more likely Wilson's random walks are to step vertically than sideways
+    #[arg(long, default_value_t = 1.0)]
+    vertical_bias: f64,
+
+    /// Fraction of the remaining walls to knock down after generating, from 0.0 for a
+    /// perfect maze to 1.0 for an open chamber
+    #[arg(long, default_value_t = 0.0)]
+    open_fraction: f64,
+
+    /// How many times the spiral algorithm's solution winds around the cylinder
+    #[arg(long, default_value_t = 1.0)]
+    turns: f64,
+
+    /// Show how far Wilson's algorithm has got, for large mazes. Only for the wilson
+    /// algorithm.
+    #[arg(long)]
+    progress: bool,
+
+    /// Put the end at the bottom cell farthest from the start, for the longest solution
+    #[arg(long)]
+    farthest_end: bool,
+
     /// Height of the cylinder
     #[arg(long, default_value_t = 60.0)]
     height: f64,
@@ -37,36 +93,253 @@ struct Args {
     /// Hollow out the inside of the cylinder, to make a container
     #[arg(long)]
     hollow: bool,
+
+    /// Cut the paths right through a hollow cylinder, leaving only the walls
+    #[arg(long)]
+    openwork: bool,
+
+    /// Number of ribs to support the inside of a hollow maze while it prints
+    #[arg(long, default_value_t = 0)]
+    support_ribs: usize,
+
+    /// Carve the maze into the inside of a tube instead of the outside of the cylinder
+    #[arg(long)]
+    inner_surface: bool,
+
+    /// Height of the base, as a fraction of the cylinder height
+    #[arg(long, default_value_t = 0.05)]
+    base_height_frac: f64,
+
+    /// Radius of the base, as a fraction of the cylinder radius
+    #[arg(long, default_value_t = 1.1)]
+    base_radius_frac: f64,
+
+    /// Width of each corridor, in multiples of the wall thickness
+    #[arg(long, default_value_t = 1, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    corridor_cells: usize,
+
+    /// Gap between the maze and the inside of the outer cylinder, in mm
+    #[arg(long, default_value_t = 0.2)]
+    clearance: f64,
+
+    /// Smallest gap allowed between the maze and the outer cylinder, around the maze and
+    /// around the tooth in the corridors, in mm. At 0, only parts that would overlap are
+    /// rejected.
+    #[arg(long, default_value_t = 0.0)]
+    min_clearance: f64,
+
+    /// Rows of vent holes through the outer cylinder
+    #[arg(long, default_value_t = 0)]
+    vent_rows: usize,
+
+    /// Columns of vent holes around the outer cylinder
+    #[arg(long, default_value_t = 0)]
+    vent_cols: usize,
+
+    /// Diameter of each vent hole, in mm
+    #[arg(long, default_value_t = 3.0)]
+    vent_diameter: f64,
+
+    /// Warn about any features thinner than a nozzle of this diameter, in mm
+    #[arg(long)]
+    nozzle: Option<f64>,
+
+    /// Print the maze with one character per cell, rather than the full grid
+    #[arg(long)]
+    compact: bool,
+
+    /// Mark the solution when printing the maze
+    #[arg(long)]
+    show_solution: bool,
+
+    /// Repeat the first column after the seam when printing the maze, to show the wrap
+    #[arg(long)]
+    show_seam: bool,
+
+    /// Character used to draw walls when printing the maze
+    #[arg(long, default_value_t = '█')]
+    wall_char: char,
+
+    /// Base filename for OpenSCAD output of the solution alone, as a tube through the maze
+    #[arg(long)]
+    solution_file: Option<String>,
+
+    /// Radius of the solution tube, in mm
+    #[arg(long, default_value_t = 0.5)]
+    solution_radius: f64,
+
+    /// Base filename for an SVG of the maze unrolled flat, for laser cutting
+    #[arg(long)]
+    svg_file: Option<String>,
+
+    /// Size of each cell in the flat SVG, in mm
+    #[arg(long, default_value_t = 5.0)]
+    svg_cell_size: f64,
+
+    /// Base filename for an SVG of the maze surface at the size of the cylinder, to print
+    /// and wrap around it
+    #[arg(long)]
+    wrap_svg_file: Option<String>,
+
+    /// Write the maze grid to this file as CSV, to inspect in a spreadsheet
+    #[arg(long)]
+    csv_file: Option<String>,
+
+    /// Write an animated GIF of the maze being generated to this file
+    #[cfg(feature = "image")]
+    #[arg(long)]
+    gif_file: Option<String>,
+}
+
+/// Generate the maze with Wilson's algorithm, recording it as a GIF if asked to
+fn generate_wilson(
+    maze: &mut CylinderMaze,
+    args: &Args,
+) -> Result<((usize, usize), (usize, usize))> {
+    let bias = VerticalBias(args.vertical_bias);
+
+    #[cfg(feature = "image")]
+    if let Some(gif_file) = &args.gif_file {
+        let mut snapshots = Vec::new();
+        let endpoints = maze.generate_wilson_with(bias, |grid| snapshots.push(grid.to_vec()));
+        maze_maker::two_d::export_gif(&snapshots, gif_file)?;
+        return Ok(endpoints);
+    }
+
+    if args.progress {
+        let mut shown = None;
+        let endpoints = maze.generate_wilson_with_progress(bias, |fraction| {
+            let percent = (fraction * 100.0) as u32;
+            if shown != Some(percent) {
+                eprint!("\rGenerating: {percent}%");
+                shown = Some(percent);
+            }
+        });
+        eprintln!();
+        return Ok(endpoints);
+    }
+
+    Ok(maze.generate_wilson_biased(bias))
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
-    let mut maze = CylinderMaze::new(args.rows, args.cols);
-    let (start, end) = maze.generate_wilson();
+    if args.vertical_bias.is_nan() || args.vertical_bias <= 0.0 {
+        bail!(
+            "--vertical-bias must be positive, not {}",
+            args.vertical_bias
+        );
+    }
+    if args.progress && !matches!(args.algorithm, Algorithm::Wilson) {
+        bail!(
+            "--progress only works with {}, not {}",
+            Algorithm::Wilson.name(),
+            args.algorithm.name()
+        );
+    }
+    if !args.turns.is_finite() || args.turns < 0.0 {
+        bail!(
+            "--turns must be a finite number of zero or more, not {}",
+            args.turns
+        );
+    }
+    if !(0.0..=1.0).contains(&args.open_fraction) {
+        bail!(
+            "--open-fraction must be between 0 and 1, not {}",
+            args.open_fraction
+        );
+    }
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut maze = CylinderMaze::with_seed(args.rows, args.cols, seed);
+    let (start, end) = match args.algorithm {
+        Algorithm::Wilson => generate_wilson(&mut maze, &args)?,
+        Algorithm::RecursiveDivision => maze.generate_recursive_division(),
+        Algorithm::Symmetric => maze.generate_symmetric(),
+        Algorithm::Spiral => maze.generate_spiral(args.turns),
+    };
+    maze.open_fraction(args.open_fraction);
+    let end = if args.farthest_end {
+        maze.move_end_farthest(start, end)
+    } else {
+        end
+    };
 
     println!(
-        "Wilson's Algorithm Maze on a Cylinder ({}x{}):",
-        args.rows, args.cols
+        "{} Maze on a Cylinder ({}x{}):",
+        args.algorithm.name(),
+        maze.rows(),
+        maze.cols()
     );
+    println!("Seed: {seed}");
     println!("(Left and right edges wrap around)");
     println!("Start (S) at top row, End (E) at bottom row\n");
-    maze.display(start, end);
-
-    println!("\nMaze is solvable: {}", maze.can_solve(start, end));
-    maze_to_openscad(
-        &maze,
-        args.height,
-        args.circumference,
-        &args.maze_file,
-        args.hollow,
-    )?;
-    make_outer_openscad(
-        args.height,
-        args.circumference,
-        maze.grid().len(),
-        maze.grid()[0].len(),
-        &args.outer_file,
-    )?;
+    if args.show_solution {
+        maze.mark_solution(start, end);
+    }
+    if args.compact {
+        maze.display_compact(start, end);
+    } else {
+        let style = DisplayStyle {
+            wall: args.wall_char,
+            repeat_seam: args.show_seam,
+            ..DisplayStyle::default()
+        };
+        maze.display_styled(start, end, &style);
+    }
+    maze.clear_solution();
+
+    println!("\nMaze is solvable: {}", maze.is_solvable());
+    println!("Complexity score: {:.3}", maze.complexity_score(start, end));
+    let config = CylinderConfig {
+        height: args.height,
+        circumference: args.circumference,
+        hollow: args.hollow,
+        base_height_frac: args.base_height_frac,
+        base_radius_frac: args.base_radius_frac,
+        corridor_cells: args.corridor_cells,
+        clearance: args.clearance,
+        vent_rows: args.vent_rows,
+        vent_cols: args.vent_cols,
+        vent_diameter: args.vent_diameter,
+        surface: if args.inner_surface {
+            MazeSurface::Inner
+        } else {
+            MazeSurface::Outer
+        },
+        support_ribs: args.support_ribs,
+        openwork: args.openwork,
+    };
+    if let Some(nozzle) = args.nozzle {
+        for problem in printability_report(&maze, &config, nozzle).problems {
+            println!("Warning: {problem}");
+        }
+    }
+    // The shell only goes over a maze on the outside of the cylinder
+    if config.surface == MazeSurface::Outer {
+        check_fit(&maze, &config, args.min_clearance)?;
+    }
+    maze_to_openscad(&maze, &config, &args.maze_file)?;
+    make_outer_openscad(&maze, &config, &args.outer_file)?;
+    if let Some(solution_file) = &args.solution_file {
+        solution_to_openscad(
+            &maze,
+            &config,
+            start,
+            end,
+            args.solution_radius,
+            solution_file,
+        )?;
+    }
+    if let Some(csv_file) = &args.csv_file {
+        maze.export_csv(csv_file)?;
+    }
+    if let Some(svg_file) = &args.svg_file {
+        export_flat_svg(&maze, args.svg_cell_size, svg_file)?;
+    }
+    if let Some(wrap_svg_file) = &args.wrap_svg_file {
+        export_wrap_svg(&maze, &config, wrap_svg_file)?;
+    }
     Ok(())
 }
@@ -0,0 +1,132 @@
+use super::{Cell, CylinderMaze, Grid};
+use anyhow::{Context, Result, bail};
+use std::io::Write;
+
+impl CylinderMaze {
+    /// Save the full grid as CSV, one grid row per line: `W` for a wall, `P` for a path and
+    /// `S` for a path marked as part of the solution.
+    ///
+    /// The grid is `2 * rows + 1` by `2 * cols + 1`. Cell (r, c) is at grid row `2 * r + 1`
+    /// and grid column `2 * c + 1`, and the squares between them are the walls. The first
+    /// and last grid columns are the same wall, at the seam where the cylinder wraps
+    /// around, so they must always match when editing the file by hand.
+    pub fn export_csv(&self, filename: &str) -> Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(filename)?);
+        for row in self.grid.iter() {
+            let line: Vec<&str> = row
+                .iter()
+                .map(|cell| match cell {
+                    Cell::Wall => "W",
+                    Cell::Path => "P",
+                    Cell::Solution => "S",
+                })
+                .collect();
+            writeln!(file, "{}", line.join(","))?;
+        }
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Load a maze from a CSV grid, as written by `export_csv` or edited by hand. The size
+    /// of the maze comes from the size of the grid, which must have an odd number of rows
+    /// and columns, with the first and last columns the same.
+    pub fn from_csv(filename: &str) -> Result<Self> {
+        let csv = std::fs::read_to_string(filename)?;
+        let mut rows = Vec::new();
+        for (line_no, line) in csv.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut row = Vec::new();
+            for value in line.split(',') {
+                row.push(match value.trim() {
+                    "W" | "w" => Cell::Wall,
+                    "P" | "p" => Cell::Path,
+                    "S" | "s" => Cell::Solution,
+                    other => bail!(
+                        "Unknown cell {other:?} on line {} of {filename}, expected W, P or S",
+                        line_no + 1
+                    ),
+                });
+            }
+            rows.push(row);
+        }
+
+        let Some(grid) = Grid::from_rows(&rows) else {
+            bail!("Maze grid in {filename} is empty or not rectangular");
+        };
+        if grid.len() < 3 || grid.len() % 2 == 0 || grid.width() < 3 || grid.width() % 2 == 0 {
+            bail!(
+                "Maze grid in {filename} is {}x{}, but must be 2n+1 by 2m+1 for a maze of n rows and m columns",
+                grid.len(),
+                grid.width()
+            );
+        }
+        let mut maze = CylinderMaze::new(grid.len() / 2, grid.width() / 2);
+        maze.grid = grid;
+        maze.check_seam()
+            .with_context(|| format!("Invalid maze in {filename}"))?;
+
+        Ok(maze)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_csv() {
+        let mut maze = CylinderMaze::new(2, 3);
+        let (start, end) = maze.generate_wilson();
+        maze.mark_solution(start, end);
+
+        let filename = std::env::temp_dir().join("maze_maker_export.csv");
+        let filename = filename.to_str().unwrap();
+        maze.export_csv(filename).unwrap();
+        let csv = std::fs::read_to_string(filename).unwrap();
+        std::fs::remove_file(filename).unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines.iter().all(|line| line.split(',').count() == 7));
+        assert!(csv.contains('S'));
+        for line in lines {
+            assert_eq!(line.chars().next(), line.chars().last());
+        }
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let mut maze = CylinderMaze::new(4, 6);
+        let (start, end) = maze.generate_wilson();
+
+        let filename = std::env::temp_dir().join("maze_maker_round_trip.csv");
+        let filename = filename.to_str().unwrap();
+        maze.export_csv(filename).unwrap();
+        let loaded = CylinderMaze::from_csv(filename).unwrap();
+        std::fs::remove_file(filename).unwrap();
+
+        assert_eq!(loaded.rows(), 4);
+        assert_eq!(loaded.cols(), 6);
+        assert_eq!(loaded.grid(), maze.grid());
+        assert!(loaded.can_solve(start, end));
+    }
+
+    #[test]
+    fn test_csv_invalid() {
+        let filename = std::env::temp_dir().join("maze_maker_invalid.csv");
+        let filename = filename.to_str().unwrap();
+        for csv in [
+            "W,W,W,W\nW,P,P,W\nW,W,W,W\n",
+            "W,W,W\nP,P,W\nW,W,W\n",
+            "W,W,W\nW,X,W\nW,W,W\n",
+            "W,W,W\nW,P\nW,W,W\n",
+        ] {
+            std::fs::write(filename, csv).unwrap();
+            assert!(CylinderMaze::from_csv(filename).is_err(), "{csv}");
+        }
+        std::fs::remove_file(filename).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use super::Cell;
+use std::ops::{Index, IndexMut};
+use std::slice::{Chunks, ChunksMut};
+
+/// The squares of a maze, stored row by row in a single allocation. Indexing by row gives
+/// a slice of that row, so squares are read and written as `grid[row][col]`.
+///
+/// This is for locality, not size: each square is still a whole `Cell` byte, so a maze
+/// takes as much memory as it did as nested `Vec`s, less the per-row overhead. Packing
+/// the squares into 2 bits each would mean giving up handing out rows as slices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    cells: Vec<Cell>,
+    width: usize,
+}
+
+impl Grid {
+    pub fn new(height: usize, width: usize, cell: Cell) -> Self {
+        Grid {
+            cells: vec![cell; height * width],
+            width,
+        }
+    }
+
+    /// Build a grid from nested rows, or `None` if there are no rows or they aren't all
+    /// the same non-zero length
+    pub fn from_rows(rows: &[Vec<Cell>]) -> Option<Self> {
+        let width = rows.first()?.len();
+        if width == 0 || rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Grid {
+            cells: rows.concat(),
+            width,
+        })
+    }
+
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.cells.len() / self.width
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Number of columns
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn iter(&self) -> Chunks<'_, Cell> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn iter_mut(&mut self) -> ChunksMut<'_, Cell> {
+        self.cells.chunks_mut(self.width)
+    }
+
+    /// Set every square to `cell`
+    pub fn fill(&mut self, cell: Cell) {
+        self.cells.fill(cell);
+    }
+
+    /// Copy row `from` over row `to`
+    pub fn copy_row(&mut self, from: usize, to: usize) {
+        let start = from * self.width;
+        self.cells
+            .copy_within(start..start + self.width, to * self.width);
+    }
+
+    /// A copy of the grid as nested rows
+    pub fn to_vec(&self) -> Vec<Vec<Cell>> {
+        self.iter().map(<[Cell]>::to_vec).collect()
+    }
+}
+
+impl Index<usize> for Grid {
+    type Output = [Cell];
+
+    fn index(&self, row: usize) -> &[Cell] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+}
+
+impl IndexMut<usize> for Grid {
+    fn index_mut(&mut self, row: usize) -> &mut [Cell] {
+        &mut self.cells[row * self.width..(row + 1) * self.width]
+    }
+}
+
+impl<'a> IntoIterator for &'a Grid {
+    type Item = &'a [Cell];
+    type IntoIter = Chunks<'a, Cell>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Grid {
+    type Item = &'a mut [Cell];
+    type IntoIter = ChunksMut<'a, Cell>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl PartialEq<Vec<Vec<Cell>>> for Grid {
+    fn eq(&self, other: &Vec<Vec<Cell>>) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a == b.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows() {
+        let mut grid = Grid::new(3, 4, Cell::Wall);
+        grid[1][2] = Cell::Path;
+        assert_eq!(grid.len(), 3);
+        assert_eq!(grid.width(), 4);
+        assert_eq!(grid[1], [Cell::Wall, Cell::Wall, Cell::Path, Cell::Wall]);
+
+        grid.copy_row(1, 2);
+        assert_eq!(grid[2], grid[1]);
+        assert_eq!(
+            grid.iter().flatten().filter(|&&c| c == Cell::Path).count(),
+            2
+        );
+        assert_eq!(grid, grid.to_vec());
+        assert_eq!(Grid::from_rows(&grid.to_vec()), Some(grid));
+    }
+
+    #[test]
+    fn test_from_rows_rejects_ragged() {
+        assert_eq!(Grid::from_rows(&[]), None);
+        assert_eq!(Grid::from_rows(&[vec![]]), None);
+        assert_eq!(
+            Grid::from_rows(&[vec![Cell::Wall; 3], vec![Cell::Wall; 2]]),
+            None
+        );
+    }
+}
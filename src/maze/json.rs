@@ -0,0 +1,127 @@
+use super::{Cell, CylinderMaze, Endpoints, Grid};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// The saved form of a maze: its size, and the full grid of walls and paths
+#[derive(Serialize, Deserialize)]
+struct SavedMaze {
+    rows: usize,
+    cols: usize,
+    grid: Vec<Vec<Cell>>,
+    /// Missing from mazes saved before the endpoints were stored
+    #[serde(default)]
+    endpoints: Option<Endpoints>,
+}
+
+impl CylinderMaze {
+    /// Save the maze grid as JSON, so it can be loaded again with `from_json`
+    pub fn to_json(&self, filename: &str) -> Result<()> {
+        let saved = SavedMaze {
+            rows: self.rows,
+            cols: self.cols,
+            grid: self.grid.to_vec(),
+            endpoints: self.endpoints,
+        };
+        std::fs::write(filename, serde_json::to_string(&saved)?)?;
+
+        Ok(())
+    }
+
+    /// Load a maze saved by `to_json`
+    pub fn from_json(filename: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(filename)?;
+        let saved: SavedMaze =
+            serde_json::from_str(&json).with_context(|| format!("Invalid maze in {filename}"))?;
+
+        let Some(grid) = Grid::from_rows(&saved.grid) else {
+            bail!("Maze grid in {filename} is empty or not rectangular");
+        };
+        let mut maze = CylinderMaze::new(saved.rows, saved.cols);
+        maze.grid = grid;
+        if !maze.has_valid_shape() {
+            bail!(
+                "Maze grid in {filename} doesn't match its size of {}x{}",
+                saved.rows,
+                saved.cols
+            );
+        }
+        maze.check_seam()
+            .with_context(|| format!("Invalid maze in {filename}"))?;
+        if let Some((start, end)) = saved.endpoints {
+            maze.validate_endpoints(start, end, false)
+                .with_context(|| format!("Invalid endpoints in {filename}"))?;
+            maze.set_endpoints(start, end);
+        }
+
+        Ok(maze)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut maze = CylinderMaze::new(5, 7);
+        let (start, end) = maze.generate_wilson();
+
+        let filename = std::env::temp_dir().join("maze_maker_round_trip.json");
+        let filename = filename.to_str().unwrap();
+        maze.to_json(filename).unwrap();
+        let loaded = CylinderMaze::from_json(filename).unwrap();
+        std::fs::remove_file(filename).unwrap();
+
+        assert_eq!(loaded.rows(), 5);
+        assert_eq!(loaded.cols(), 7);
+        assert_eq!(loaded.grid(), maze.grid());
+        assert!(loaded.can_solve(start, end));
+        assert_eq!((loaded.start(), loaded.end()), (Some(start), Some(end)));
+        assert!(loaded.is_solvable());
+    }
+
+    #[test]
+    fn test_json_without_endpoints() {
+        let filename = std::env::temp_dir().join("maze_maker_without_endpoints.json");
+        let filename = filename.to_str().unwrap();
+        std::fs::write(
+            filename,
+            r#"{"rows":1,"cols":1,"grid":[["Wall","Wall","Wall"],["Wall","Path","Wall"],["Wall","Wall","Wall"]]}"#,
+        )
+        .unwrap();
+        let loaded = CylinderMaze::from_json(filename).unwrap();
+        std::fs::remove_file(filename).unwrap();
+
+        assert_eq!(loaded.start(), None);
+    }
+
+    #[test]
+    fn test_json_wrong_size() {
+        let filename = std::env::temp_dir().join("maze_maker_wrong_size.json");
+        let filename = filename.to_str().unwrap();
+        std::fs::write(
+            filename,
+            r#"{"rows":2,"cols":1,"grid":[["Wall","Wall","Wall"]]}"#,
+        )
+        .unwrap();
+        let loaded = CylinderMaze::from_json(filename);
+        std::fs::remove_file(filename).unwrap();
+
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn test_json_broken_seam() {
+        let filename = std::env::temp_dir().join("maze_maker_broken_seam.json");
+        let filename = filename.to_str().unwrap();
+        std::fs::write(
+            filename,
+            r#"{"rows":1,"cols":1,"grid":[["Wall","Wall","Wall"],["Path","Path","Wall"],["Wall","Wall","Wall"]]}"#,
+        )
+        .unwrap();
+        let loaded = CylinderMaze::from_json(filename);
+        std::fs::remove_file(filename).unwrap();
+
+        assert!(loaded.is_err());
+    }
+}
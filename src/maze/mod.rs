@@ -1,263 +1,2828 @@
-use rand::Rng;
-use std::collections::{HashSet, VecDeque};
+mod csv;
+mod grid;
+#[cfg(feature = "serde")]
+mod json;
+mod rng;
+
+use anyhow::{Result, bail};
+pub use grid::Grid;
+use rng::MazeRng;
+use std::cell::OnceCell;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cell {
     Wall,
     Path,
+    /// A path that's part of the solution, marked by `mark_solution`
+    Solution,
+}
+
+impl Cell {
+    /// Whether the square can be moved through, which a solution square can
+    pub fn is_open(self) -> bool {
+        matches!(self, Cell::Path | Cell::Solution)
+    }
+}
+
+/// A direction of movement from one cell to the next. Left and right wrap around the
+/// cylinder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// Every direction, in the order neighbors are listed by `get_neighbors_with_dir`
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// The direction back the way this one came
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// The change in `(row, col)` for one step this way, before wrapping around the seam.
+    /// Rows count down the cylinder, so up is -1.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+
+    /// The cell one step this way from `cell` in `maze`, wrapping around the seam, or
+    /// `None` off the top or bottom. Walls aren't checked.
+    pub fn apply(self, maze: &CylinderMaze, cell: (usize, usize)) -> Option<(usize, usize)> {
+        let (dr, dc) = self.delta();
+        let row = cell
+            .0
+            .checked_add_signed(dr)
+            .filter(|&row| row < maze.rows)?;
+        let col = (cell.1 + maze.cols).checked_add_signed(dc)? % maze.cols;
+        Some((row, col))
+    }
+
+    /// The direction after a quarter turn clockwise, looking at the grid as displayed
+    fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    /// The direction after a quarter turn counterclockwise
+    fn turn_left(self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+}
+
+/// How much each measure of a maze counts towards `complexity_score_weighted`. Each
+/// measure is scaled to be roughly between 0 and 1 before it's weighted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComplexityWeights {
+    /// Weight of the fraction of cells on the solution
+    pub solution_length: f64,
+    /// Weight of the fraction of cells that are dead ends
+    pub dead_ends: f64,
+    /// Weight of the fraction of cells that are junctions of three or more passages
+    pub branch_points: f64,
+    /// Weight of how far, on average, a dead end is from the solution, as a fraction of
+    /// `rows + cols`
+    pub branch_depth: f64,
+}
+
+impl Default for ComplexityWeights {
+    fn default() -> Self {
+        ComplexityWeights {
+            solution_length: 0.4,
+            dead_ends: 0.2,
+            branch_points: 0.2,
+            branch_depth: 0.2,
+        }
+    }
+}
+
+/// How `display_styled` draws the maze: a character for each kind of grid square, and
+/// whether to show the wrap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayStyle {
+    pub wall: char,
+    pub path: char,
+    pub start: char,
+    pub end: char,
+    pub solution: char,
+    /// Draw the first column of cells again after the seam on the right, to show that
+    /// the sides join up
+    pub repeat_seam: bool,
+}
+
+impl Default for DisplayStyle {
+    fn default() -> Self {
+        DisplayStyle {
+            wall: '█',
+            path: ' ',
+            start: 'S',
+            end: 'E',
+            solution: '.',
+            repeat_seam: false,
+        }
+    }
+}
+
+/// How much more likely a random walk is to step up or down than sideways. A bias of
+/// 1.0 treats every direction the same, and larger values make mazes whose passages
+/// mostly run from top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VerticalBias(pub f64);
+
+impl Default for VerticalBias {
+    fn default() -> Self {
+        VerticalBias(1.0)
+    }
 }
 
+/// A copy of the maze grid, taken while the maze is being generated
+pub type GridSnapshot = Vec<Vec<Cell>>;
+
+/// The start and end cells of a maze
+pub type Endpoints = ((usize, usize), (usize, usize));
+
 pub struct CylinderMaze {
-    grid: Vec<Vec<Cell>>,
+    grid: Grid,
     rows: usize,
     cols: usize,
+    rng: MazeRng,
+    /// The start and end the maze was generated with, or set with `set_endpoints`
+    endpoints: Option<Endpoints>,
+    /// The route between `endpoints`, worked out the first time it's asked for
+    solution: OnceCell<Option<Vec<(usize, usize)>>>,
 }
 
 impl CylinderMaze {
     pub fn new(rows: usize, cols: usize) -> Self {
+        Self::with_seed(rows, cols, rand::random())
+    }
+
+    /// An empty maze whose generators are driven by `seed`. The same seed, size and
+    /// generator calls always make the same maze, on any platform and with any version of
+    /// `rand`.
+    pub fn with_seed(rows: usize, cols: usize, seed: u64) -> Self {
         // Create grid with walls and paths: (2*rows + 1) x (2*cols + 1)
         // Odd positions are cells, even positions are walls
         let grid_rows = 2 * rows + 1;
         let grid_cols = 2 * cols + 1;
         CylinderMaze {
-            grid: vec![vec![Cell::Wall; grid_cols]; grid_rows],
+            grid: Grid::new(grid_rows, grid_cols, Cell::Wall),
             rows,
             cols,
+            rng: MazeRng::from_seed(seed),
+            endpoints: None,
+            solution: OnceCell::new(),
         }
     }
 
-    pub fn grid(&self) -> &Vec<Vec<Cell>> {
+    /// Make a `rows` by `cols` maze for each of `seeds`, in order, with `generate`, along
+    /// with the start and end it returns. The mazes are shared out between threads, and
+    /// each is the same as it would be made on its own with the same seed.
+    pub fn generate_batch<F>(
+        rows: usize,
+        cols: usize,
+        seeds: &[u64],
+        generate: F,
+    ) -> Vec<(CylinderMaze, Endpoints)>
+    where
+        F: Fn(&mut CylinderMaze) -> Endpoints + Sync,
+    {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = seeds.len().div_ceil(threads).max(1);
+        let generate = &generate;
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = seeds
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|&seed| {
+                                let mut maze = CylinderMaze::with_seed(rows, cols, seed);
+                                let endpoints = generate(&mut maze);
+                                (maze, endpoints)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("Maze generation panicked"))
+                .collect()
+        })
+    }
+
+    /// The lowest seed below `max_seeds` whose `rows` by `cols` maze, made with `generate`,
+    /// satisfies `target`, such as having a particular `grid_hash` or a high enough
+    /// complexity. The seeds are shared out between threads, which stop once they're past
+    /// the lowest match so far. `None` if no seed matches.
+    pub fn find_seed<F, P>(
+        rows: usize,
+        cols: usize,
+        generate: F,
+        target: P,
+        max_seeds: u64,
+    ) -> Option<u64>
+    where
+        F: Fn(&mut CylinderMaze) -> Endpoints + Sync,
+        P: Fn(&CylinderMaze) -> bool + Sync,
+    {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get()) as u64;
+        let found = AtomicU64::new(u64::MAX);
+        let (generate, target, found) = (&generate, &target, &found);
+
+        std::thread::scope(|scope| {
+            for first in 0..threads {
+                scope.spawn(move || {
+                    // Each thread takes every `threads`th seed, in order
+                    let mut seed = first;
+                    while seed < max_seeds && seed < found.load(Ordering::Relaxed) {
+                        let mut maze = CylinderMaze::with_seed(rows, cols, seed);
+                        generate(&mut maze);
+                        if target(&maze) {
+                            found.fetch_min(seed, Ordering::Relaxed);
+                            break;
+                        }
+                        seed += threads;
+                    }
+                });
+            }
+        });
+
+        let seed = found.load(Ordering::Relaxed);
+        (seed != u64::MAX).then_some(seed)
+    }
+
+    /// Restart the random numbers from `seed`, so the next generator call makes the same
+    /// maze as it would in `CylinderMaze::with_seed(rows, cols, seed)`
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = MazeRng::from_seed(seed);
+    }
+
+    /// Set the start and end that `solution` finds a route between. The generators set
+    /// them, so this is only needed to solve between other cells.
+    pub fn set_endpoints(&mut self, start: (usize, usize), end: (usize, usize)) {
+        self.endpoints = Some((start, end));
+        self.solution.take();
+    }
+
+    /// The start cell the maze was generated with, or set with `set_endpoints`
+    pub fn start(&self) -> Option<(usize, usize)> {
+        self.endpoints.map(|(start, _)| start)
+    }
+
+    /// The end cell the maze was generated with, or set with `set_endpoints`
+    pub fn end(&self) -> Option<(usize, usize)> {
+        self.endpoints.map(|(_, end)| end)
+    }
+
+    /// Whether there's a route from the maze's own start to its end
+    pub fn is_solvable(&self) -> bool {
+        self.solution().is_some()
+    }
+
+    /// The shortest route from the start to the end, including both, or `None` if there's
+    /// no route or no endpoints have been set. It's found once and kept until the maze
+    /// changes, so exporters can all ask for it without solving the maze again.
+    pub fn solution(&self) -> Option<&[(usize, usize)]> {
+        let (start, end) = self.endpoints?;
+        self.solution
+            .get_or_init(|| self.solve_path(start, end))
+            .as_deref()
+    }
+
+    pub fn grid(&self) -> &Grid {
         &self.grid
     }
 
-    fn cell_to_grid(&self, row: usize, col: usize) -> (usize, usize) {
-        (2 * row + 1, 2 * col + 1)
+    /// Whether the grid is `2 * rows + 1` by `2 * cols + 1`, which all the code that maps
+    /// between cells and grid squares relies on
+    pub fn has_valid_shape(&self) -> bool {
+        self.grid.len() == 2 * self.rows + 1 && self.grid.width() == 2 * self.cols + 1
     }
 
-    fn get_neighbors(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        let mut neighbors = Vec::new();
+    /// Check that the first and last grid columns match in every row, since they're the
+    /// same wall at the seam. The generators always keep them the same, but a grid loaded
+    /// from a file might not.
+    pub(super) fn check_seam(&self) -> Result<()> {
+        let last = self.grid.width() - 1;
+        if let Some(row) = self.grid.iter().position(|row| row[0] != row[last]) {
+            bail!(
+                "First and last columns of grid row {} differ, but are the same wall at the seam",
+                row + 1
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether a grid column is part of the seam. The first and last grid columns are the
+    /// same wall, where the cylinder wraps around, so a renderer should draw only one of
+    /// them, or treat them as one.
+    pub fn is_seam_column(&self, grid_col: usize) -> bool {
+        grid_col == 0 || grid_col == self.grid[0].len() - 1
+    }
+
+    /// Shift every column `by` places around the cylinder, which moves the seam. The cell at
+    /// `(row, col)` moves to `(row, (col + by) % cols)`. The maze's own start and end move
+    /// with their cells, but endpoints kept elsewhere need moving to match.
+    pub fn rotate_columns(&mut self, by: usize) {
+        if self.cols == 0 {
+            return;
+        }
+        let by = by % self.cols;
 
-        // Up
-        if row > 0 {
-            neighbors.push((row - 1, col));
+        // Each row is a ring of walls and cells, with the seam repeated at the end
+        let ring = 2 * self.cols;
+        let shift = 2 * by;
+        for row in &mut self.grid {
+            row[..ring].rotate_right(shift);
+            row[ring] = row[0];
         }
-        // Down
-        if row < self.rows - 1 {
-            neighbors.push((row + 1, col));
+        if let Some((start, end)) = self.endpoints {
+            let rotate = |(row, col): (usize, usize)| (row, (col + by) % self.cols);
+            self.set_endpoints(rotate(start), rotate(end));
         }
-        // Left (wraps around cylinder)
-        let left_col = if col == 0 { self.cols - 1 } else { col - 1 };
-        neighbors.push((row, left_col));
+    }
 
-        // Right (wraps around cylinder)
-        let right_col = (col + 1) % self.cols;
-        neighbors.push((row, right_col));
+    /// Copy the cells in `rows` and `cols`, and the walls between them, into a new maze.
+    /// Unless the crop spans every column, the seam is closed so it doesn't wrap, and the
+    /// top and bottom edges are closed unless they're edges of this maze. The cropped maze
+    /// may be in several disconnected pieces.
+    pub fn subgrid(&self, rows: Range<usize>, cols: Range<usize>) -> CylinderMaze {
+        assert!(
+            !rows.is_empty() && rows.end <= self.rows && !cols.is_empty() && cols.end <= self.cols,
+            "Crop {rows:?}x{cols:?} must be inside the {}x{} maze",
+            self.rows,
+            self.cols
+        );
+        let mut maze = CylinderMaze::new(rows.len(), cols.len());
+        for (r, gr) in (2 * rows.start..=2 * rows.end).enumerate() {
+            for (c, gc) in (2 * cols.start..=2 * cols.end).enumerate() {
+                maze.grid[r][c] = self.grid[gr][gc];
+            }
+        }
 
-        neighbors
+        let grid_rows = maze.grid.len();
+        let grid_cols = maze.grid.width();
+        if cols.len() < self.cols {
+            for row in &mut maze.grid {
+                row[0] = Cell::Wall;
+                row[grid_cols - 1] = Cell::Wall;
+            }
+        }
+        if rows.start > 0 {
+            maze.grid[0].fill(Cell::Wall);
+        }
+        if rows.end < self.rows {
+            maze.grid[grid_rows - 1].fill(Cell::Wall);
+        }
+        maze
     }
 
-    fn carve_passage(&mut self, from: (usize, usize), to: (usize, usize)) {
-        let (from_r, from_c) = self.cell_to_grid(from.0, from.1);
-        let (to_r, to_c) = self.cell_to_grid(to.0, to.1);
+    /// Set every square in the grid back to a wall, and forget the start and end, so the
+    /// maze can be generated again
+    pub fn reset(&mut self) {
+        self.grid.fill(Cell::Wall);
+        self.endpoints = None;
+        self.solution.take();
+    }
 
-        // Mark both cells as paths
-        self.grid[from_r][from_c] = Cell::Path;
-        self.grid[to_r][to_c] = Cell::Path;
+    /// The logical cell at `(row, col)`, or `None` if it's outside the maze
+    pub fn cell(&self, row: usize, col: usize) -> Option<Cell> {
+        if row < self.rows && col < self.cols {
+            let (gr, gc) = self.cell_to_grid(row, col);
+            Some(self.grid[gr][gc])
+        } else {
+            None
+        }
+    }
 
-        // Mark the wall between them as path
-        // Handle wrapping for horizontal movement
-        if from.0 == to.0 {
-            // Horizontal movement
-            if (from.1 == 0 && to.1 == self.cols - 1) || (from.1 == self.cols - 1 && to.1 == 0) {
-                // Wrapping around cylinder - connect through leftmost and rightmost walls
-                // The leftmost wall (column 0) and rightmost wall (column grid_cols-1) are the same
-                let grid_cols = self.grid[0].len();
-                self.grid[from_r][0] = Cell::Path;
-                self.grid[from_r][grid_cols - 1] = Cell::Path;
+    /// A fingerprint of the maze's shape and walls, for spotting duplicates. This is
+    /// 64-bit FNV-1a over the dimensions and every grid square, so it's the same on every
+    /// run and platform, unlike `std`'s randomly seeded hasher.
+    pub fn grid_hash(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let dimensions = [self.rows as u64, self.cols as u64];
+        let bytes = dimensions
+            .iter()
+            .flat_map(|n| n.to_le_bytes())
+            .chain(self.grid.iter().flatten().map(|&cell| cell as u8));
+        bytes.fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+    }
+
+    /// Number of rows of cells in the maze. The grid has `2 * rows + 1` rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns of cells around the maze. The grid has `2 * cols + 1` columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Whether the two logical cells `a` and `b` are adjacent and connected by a passage,
+    /// including across the seam where the cylinder wraps around. With two columns, the
+    /// cells in a row are joined both ways, and either passage counts.
+    pub fn is_open(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        if a.0 >= self.rows || a.1 >= self.cols || b.0 >= self.rows || b.1 >= self.cols {
+            return false;
+        }
+
+        // With a single column, the seam would join a cell to itself
+        a != b && self.open_direction(a, b).is_some()
+    }
+
+    /// The cells of the maze as a plain `rows` by `cols` grid, without the walls between
+    /// them. With `repeat_seam`, the first column is repeated after the last one, to show
+    /// that the grid wraps around.
+    pub fn to_flat_grid(&self, repeat_seam: bool) -> Vec<Vec<Cell>> {
+        (0..self.rows)
+            .map(|row| {
+                let mut cells: Vec<Cell> = (0..self.cols)
+                    .map(|col| {
+                        let (gr, gc) = self.cell_to_grid(row, col);
+                        self.grid[gr][gc]
+                    })
+                    .collect();
+                if repeat_seam {
+                    cells.push(cells[0]);
+                }
+                cells
+            })
+            .collect()
+    }
+
+    /// The full grid as booleans, `true` for a wall and `false` for a path, for use without
+    /// the `Cell` type. It has `2 * rows + 1` rows of `2 * cols + 1` squares: cells are at
+    /// odd positions, the walls between them at even ones, and the first and last columns
+    /// are both the seam.
+    pub fn wall_mask(&self) -> Vec<Vec<bool>> {
+        self.grid
+            .iter()
+            .map(|row| row.iter().map(|&cell| cell == Cell::Wall).collect())
+            .collect()
+    }
+
+    /// The number of rows and columns in `scaled_grid(corridor_cells)`, without making it
+    pub fn scaled_size(&self, corridor_cells: usize) -> (usize, usize) {
+        assert!(
+            corridor_cells > 0,
+            "Corridors must be at least one cell wide"
+        );
+        (
+            self.rows * (corridor_cells + 1) + 1,
+            self.cols * (corridor_cells + 1) + 1,
+        )
+    }
+
+    /// The square at `(row, col)` of `scaled_grid(corridor_cells)`, read straight from the
+    /// grid without making the scaled copy
+    pub fn scaled_cell(&self, corridor_cells: usize, row: usize, col: usize) -> Cell {
+        // Each wall is followed by `corridor_cells` copies of a cell
+        let unscale = |i: usize| {
+            let (block, offset) = (i / (corridor_cells + 1), i % (corridor_cells + 1));
+            if offset == 0 {
+                2 * block
             } else {
-                let wall_c = (from_c + to_c) / 2;
-                self.grid[from_r][wall_c] = Cell::Path;
+                2 * block + 1
             }
-        } else {
-            // Vertical movement
-            let wall_r = (from_r + to_r) / 2;
-            self.grid[wall_r][from_c] = Cell::Path;
-        }
+        };
+        self.grid[unscale(row)][unscale(col)]
     }
 
-    pub fn generate_wilson(&mut self) -> ((usize, usize), (usize, usize)) {
-        let mut rng = rand::thread_rng();
-        let mut in_maze = HashSet::new();
+    /// The grid with every row and column of cells repeated `corridor_cells` times, so the
+    /// corridors are that many squares wide while the walls between them stay one square
+    /// thick
+    pub fn scaled_grid(&self, corridor_cells: usize) -> Vec<Vec<Cell>> {
+        assert!(
+            corridor_cells > 0,
+            "Corridors must be at least one cell wide"
+        );
+        let repeats = |i: usize| if i % 2 == 1 { corridor_cells } else { 1 };
 
-        // Start with a random cell in the top row
-        let start_row = 0;
-        let start_col = rng.gen_range(0..self.cols);
-        in_maze.insert((start_row, start_col));
-        let (gr, gc) = self.cell_to_grid(start_row, start_col);
-        self.grid[gr][gc] = Cell::Path;
+        let mut scaled = Vec::new();
+        for (r, row) in self.grid.iter().enumerate() {
+            let scaled_row: Vec<Cell> = row
+                .iter()
+                .enumerate()
+                .flat_map(|(c, &cell)| std::iter::repeat_n(cell, repeats(c)))
+                .collect();
+            for _ in 0..repeats(r) {
+                scaled.push(scaled_row.clone());
+            }
+        }
+        scaled
+    }
 
-        // Add all other cells
+    /// Number of open walls between pairs of adjacent cells, counting the seam once. A
+    /// perfect maze is a spanning tree of the cells, so it has `rows * cols - 1` passages.
+    pub fn passage_count(&self) -> usize {
+        let mut count = 0;
         for row in 0..self.rows {
             for col in 0..self.cols {
-                if in_maze.contains(&(row, col)) {
-                    continue;
-                }
+                let (gr, gc) = self.cell_to_grid(row, col);
 
-                // Perform loop-erased random walk
-                let mut path = vec![(row, col)];
-                let mut current = (row, col);
+                // Passage to the cell below
+                if row + 1 < self.rows && self.grid[gr + 1][gc].is_open() {
+                    count += 1;
+                }
 
-                while !in_maze.contains(&current) {
-                    let neighbors = self.get_neighbors(current.0, current.1);
-                    let next = neighbors[rng.gen_range(0..neighbors.len())];
+                // Passage to the cell to the right, which is through the seam for the last
+                // column. With a single column the seam would join a cell to itself.
+                let right = if col == self.cols - 1 { 0 } else { gc + 1 };
+                if self.cols > 1 && self.grid[gr][right].is_open() {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
 
-                    // Check if we've visited this cell in current walk
-                    if let Some(pos) = path.iter().position(|&p| p == next) {
-                        // Loop detected - erase the loop
-                        path.truncate(pos + 1);
-                    } else {
-                        path.push(next);
-                    }
+    /// The fraction of open squares in logical column `col`: the grid column of its cells
+    /// and the wall column to their right, leaving out the top and bottom edges. Busier
+    /// columns are closer to 1.0, so rotating the least dense one to the seam with
+    /// `rotate_columns` hides the seam best.
+    pub fn column_path_density(&self, col: usize) -> f64 {
+        assert!(
+            col < self.cols,
+            "Column {col} is outside the {} columns of the maze",
+            self.cols
+        );
+        let (_, gc) = self.cell_to_grid(0, col);
+        let rows = 1..self.grid.len() - 1;
+        let open = rows
+            .clone()
+            .flat_map(|gr| [self.grid[gr][gc], self.grid[gr][gc + 1]])
+            .filter(|cell| cell.is_open())
+            .count();
+        open as f64 / (2 * rows.len()) as f64
+    }
 
-                    current = next;
-                }
+    /// Number of steps along the passages from `start` to every cell, or `None` for cells
+    /// that can't be reached
+    pub fn distances_from(&self, start: (usize, usize)) -> Vec<Vec<Option<usize>>> {
+        let mut distances = vec![vec![None; self.cols]; self.rows];
+        let mut queue = VecDeque::new();
 
-                // Add the path to the maze by carving passages
-                for i in 0..path.len() {
-                    let cell = path[i];
-                    in_maze.insert(cell);
+        distances[start.0][start.1] = Some(0);
+        queue.push_back(start);
 
-                    if i > 0 {
-                        self.carve_passage(path[i - 1], cell);
-                    }
+        while let Some((row, col)) = queue.pop_front() {
+            let distance = distances[row][col].unwrap_or(0);
+            for (nr, nc) in self.open_neighbors(row, col) {
+                if distances[nr][nc].is_none() {
+                    distances[nr][nc] = Some(distance + 1);
+                    queue.push_back((nr, nc));
                 }
             }
         }
 
-        // Pick a random cell in the bottom row as the end
-        let end_row = self.rows - 1;
-        let end_col = rng.gen_range(0..self.cols);
+        distances
+    }
 
-        // Open the top wall at the start position
-        let (_, start_grid_col) = self.cell_to_grid(start_row, start_col);
-        self.grid[0][start_grid_col] = Cell::Path;
+    /// The cells at most `max_steps` steps along the passages from `from`, including it.
+    /// Like `distances_from`, but it stops looking once it's `max_steps` away, so it's
+    /// cheap for a small neighborhood of a big maze.
+    pub fn reachable_within(
+        &self,
+        from: (usize, usize),
+        max_steps: usize,
+    ) -> HashSet<(usize, usize)> {
+        let mut reached = HashSet::from([from]);
+        let mut queue = VecDeque::from([(from, 0)]);
 
-        // Open the bottom wall at the end position
-        let (_, end_grid_col) = self.cell_to_grid(end_row, end_col);
-        let bottom_row = self.grid.len() - 1;
-        self.grid[bottom_row][end_grid_col] = Cell::Path;
+        while let Some(((row, col), steps)) = queue.pop_front() {
+            if steps == max_steps {
+                continue;
+            }
+            for neighbor in self.open_neighbors(row, col) {
+                if reached.insert(neighbor) {
+                    queue.push_back((neighbor, steps + 1));
+                }
+            }
+        }
 
-        ((start_row, start_col), (end_row, end_col))
+        reached
     }
 
-    pub fn display(&self, start: (usize, usize), end: (usize, usize)) {
-        let (start_r, start_c) = self.cell_to_grid(start.0, start.1);
-        let (end_r, end_c) = self.cell_to_grid(end.0, end.1);
+    /// How many logical path cells can't be reached from the first one, which is always
+    /// zero for a perfect maze. Anything else means part of the maze is sealed off.
+    pub fn unreachable_cell_count(&self) -> usize {
+        let paths: Vec<(usize, usize)> = (0..self.rows)
+            .flat_map(|row| (0..self.cols).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.cell(row, col).is_some_and(Cell::is_open))
+            .collect();
+        let Some(&first) = paths.first() else {
+            return 0;
+        };
+        let distances = self.distances_from(first);
+        paths
+            .iter()
+            .filter(|&&(row, col)| distances[row][col].is_none())
+            .count()
+    }
 
-        for (r, row) in self.grid.iter().enumerate() {
-            for (c, cell) in row.iter().enumerate() {
-                if (r, c) == (start_r, start_c) {
-                    print!("S");
-                } else if (r, c) == (end_r, end_c) {
-                    print!("E");
-                } else {
-                    match cell {
-                        Cell::Wall => print!("█"),
-                        Cell::Path => print!(" "),
+    /// The longest route between any two cells, as its length in steps and the cells at
+    /// either end. In a perfect maze this is the hardest possible start and end. Found
+    /// with two passes: the farthest cell from anywhere is one end of the longest route,
+    /// and the farthest cell from that is the other. If the maze is in pieces, this is
+    /// the longest route in the piece with the first open cell.
+    pub fn longest_path(&self) -> (usize, (usize, usize), (usize, usize)) {
+        let farthest = |from: (usize, usize)| {
+            let distances = self.distances_from(from);
+            let mut best = (0, from);
+            for (row, row_distances) in distances.iter().enumerate() {
+                for (col, distance) in row_distances.iter().enumerate() {
+                    if let Some(distance) = *distance
+                        && distance > best.0
+                    {
+                        best = (distance, (row, col));
                     }
                 }
             }
-            println!();
-        }
+            best
+        };
+
+        // Start from a cell that's part of the maze. Only an empty maze has none.
+        let mut cells = (0..self.rows).flat_map(|row| (0..self.cols).map(move |col| (row, col)));
+        let Some(open) = cells.find(|&(row, col)| self.cell(row, col).is_some_and(Cell::is_open))
+        else {
+            return (0, (0, 0), (0, 0));
+        };
+        let (_, first) = farthest(open);
+        let (length, second) = farthest(first);
+        (length, first, second)
     }
 
-    pub fn can_solve(&self, start: (usize, usize), end: (usize, usize)) -> bool {
-        let (start_r, start_c) = self.cell_to_grid(start.0, start.1);
-        let (end_r, end_c) = self.cell_to_grid(end.0, end.1);
+    /// Move the end to the cell in the bottom row that is farthest from `start`, so the
+    /// solution is as long as possible, and return it. The bottom wall below the old end
+    /// is closed, and opened below the new one.
+    pub fn move_end_farthest(
+        &mut self,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> (usize, usize) {
+        let distances = self.distances_from(start);
+        let bottom = self.rows - 1;
+        let farthest = (0..self.cols)
+            .max_by_key(|&col| distances[bottom][col])
+            .map_or(end, |col| (bottom, col));
+
+        let bottom_row = self.grid.len() - 1;
+        let (_, old_col) = self.cell_to_grid(end.0, end.1);
+        let (_, new_col) = self.cell_to_grid(farthest.0, farthest.1);
+        self.grid[bottom_row][old_col] = Cell::Wall;
+        self.grid[bottom_row][new_col] = Cell::Path;
+        self.solution.take();
+        if self.endpoints == Some((start, end)) {
+            self.set_endpoints(start, farthest);
+        }
+        farthest
+    }
 
+    /// The shortest route along the passages from `start` to `end`, including both, or
+    /// `None` if there isn't one
+    pub fn solve_path(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        let mut previous = vec![vec![None; self.cols]; self.rows];
         let mut queue = VecDeque::new();
-        let mut visited = HashSet::new();
 
-        queue.push_back((start_r, start_c));
-        visited.insert((start_r, start_c));
+        previous[start.0][start.1] = Some(start);
+        queue.push_back(start);
 
-        let grid_rows = self.grid.len();
-        let grid_cols = self.grid[0].len();
+        while let Some(cell) = queue.pop_front() {
+            if cell == end {
+                // Walk back from the end to the start
+                let mut path = vec![end];
+                let mut current = end;
+                while current != start {
+                    current = previous[current.0][current.1]?;
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
 
-        while let Some((r, c)) = queue.pop_front() {
-            if (r, c) == (end_r, end_c) {
-                return true;
+            for (nr, nc) in self.open_neighbors(cell.0, cell.1) {
+                if previous[nr][nc].is_none() {
+                    previous[nr][nc] = Some(cell);
+                    queue.push_back((nr, nc));
+                }
             }
+        }
 
-            // Check all four directions
-            let mut neighbors = Vec::new();
+        None
+    }
 
-            // Up
-            if r > 0 {
-                neighbors.push((r - 1, c));
+    /// The cheapest route from `start` to `end`, and its cost, where each step costs
+    /// `cost` of the direction it's in. Making `Direction::Up` expensive finds the easiest
+    /// way down for a marble. Only mazes with loops have more than one route to choose
+    /// from. `None` if there isn't a route, or every route costs more than `u32::MAX`.
+    pub fn solve_weighted(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+        cost: impl Fn(Direction) -> u32,
+    ) -> Option<(Vec<(usize, usize)>, u32)> {
+        let mut best = vec![vec![None; self.cols]; self.rows];
+        let mut previous = vec![vec![None; self.cols]; self.rows];
+        let mut queue = BinaryHeap::new();
+
+        best[start.0][start.1] = Some(0);
+        queue.push(Reverse((0, start)));
+
+        while let Some(Reverse((total, cell))) = queue.pop() {
+            if cell == end {
+                // Walk back from the end to the start
+                let mut path = vec![end];
+                let mut current = end;
+                while current != start {
+                    current = previous[current.0][current.1]?;
+                    path.push(current);
+                }
+                path.reverse();
+                return Some((path, total));
             }
-            // Down
-            if r + 1 < grid_rows {
-                neighbors.push((r + 1, c));
+            if best[cell.0][cell.1].is_some_and(|b| b < total) {
+                continue;
             }
-            // Left (with wrapping)
-            let left_c = if c == 0 { grid_cols - 1 } else { c - 1 };
-            neighbors.push((r, left_c));
-
-            // Right (with wrapping)
-            let right_c = (c + 1) % grid_cols;
-            neighbors.push((r, right_c));
 
-            for (nr, nc) in neighbors {
-                if !visited.contains(&(nr, nc)) && self.grid[nr][nc] == Cell::Path {
-                    visited.insert((nr, nc));
-                    queue.push_back((nr, nc));
+            for (dir, (nr, nc)) in self.get_neighbors_with_dir(cell.0, cell.1) {
+                let (wr, wc) = self.wall_toward(cell.0, cell.1, dir);
+                if (nr, nc) == cell || !self.grid[wr][wc].is_open() {
+                    continue;
+                }
+                // A route that costs more than a u32 can count is no route at all
+                let Some(next_total) = total.checked_add(cost(dir)) else {
+                    continue;
+                };
+                if best[nr][nc].is_none_or(|b| next_total < b) {
+                    best[nr][nc] = Some(next_total);
+                    previous[nr][nc] = Some(cell);
+                    queue.push(Reverse((next_total, (nr, nc))));
                 }
             }
         }
 
-        false
+        None
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// The grid squares along the shortest route from `start` to `end`, in order: each
+    /// cell, then the open wall to the next one. Crossing the seam goes through both of
+    /// its columns, on the side it leaves and then the side it arrives. `None` if there
+    /// isn't a route.
+    pub fn solution_squares(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        let path = self.solve_path(start, end)?;
+        let grid_cols = self.grid.width();
+        let mut squares = Vec::new();
+        for step in path.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            squares.push(self.cell_to_grid(from.0, from.1));
 
-    #[test]
-    fn test_maze_is_solvable() {
-        // Generate multiple mazes and verify they're all solvable
-        for _ in 0..10 {
-            let mut maze = CylinderMaze::new(10, 10);
-            let (start, end) = maze.generate_wilson();
+            let dir = self
+                .open_direction(from, to)
+                .expect("Steps of the solution are open to each other");
+            let (wr, wc) = self.wall_toward(from.0, from.1, dir);
+            squares.push((wr, wc));
+            if wc == 0 {
+                squares.push((wr, grid_cols - 1));
+            } else if wc == grid_cols - 1 {
+                squares.push((wr, 0));
+            }
+        }
+        squares.push(self.cell_to_grid(end.0, end.1));
+        Some(squares)
+    }
 
-            assert!(
-                maze.can_solve(start, end),
-                "Maze should be solvable from S to E"
-            );
+    /// Mark the squares on the shortest route from `start` to `end` as `Cell::Solution`,
+    /// including the walls opened between them, so the solution is part of the grid.
+    /// Returns `false`, and marks nothing, if there's no route.
+    pub fn mark_solution(&mut self, start: (usize, usize), end: (usize, usize)) -> bool {
+        let Some(squares) = self.solution_squares(start, end) else {
+            return false;
+        };
+        for (gr, gc) in squares {
+            self.grid[gr][gc] = Cell::Solution;
         }
+        true
     }
 
-    #[test]
-    fn test_small_maze_solvable() {
-        let mut maze = CylinderMaze::new(3, 3);
-        let (start, end) = maze.generate_wilson();
+    /// Turn any squares marked as the solution back into plain paths
+    pub fn clear_solution(&mut self) {
+        for cell in self.grid.iter_mut().flatten() {
+            if *cell == Cell::Solution {
+                *cell = Cell::Path;
+            }
+        }
+    }
 
-        assert!(
+    /// Walk from `start` to `end` keeping a hand on the right-hand wall, and return every
+    /// cell visited in order, including backtracking out of dead ends. The walk starts
+    /// facing down the cylinder. It's guaranteed to reach the end of a perfect maze, but
+    /// can circle forever in a maze with loops, so it gives up and returns `None` after
+    /// visiting `4 * rows * cols` cells.
+    pub fn solve_wall_follower(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        let max_steps = 4 * self.rows * self.cols;
+        let mut path = vec![start];
+        let mut current = start;
+        let mut facing = Direction::Down;
+
+        while current != end {
+            if path.len() > max_steps {
+                return None;
+            }
+
+            // Try right, straight on, left, then back the way we came
+            let open: Vec<(Direction, (usize, usize))> = self
+                .get_neighbors_with_dir(current.0, current.1)
+                .into_iter()
+                .filter(|&(dir, neighbor)| {
+                    let (wr, wc) = self.wall_toward(current.0, current.1, dir);
+                    neighbor != current && self.grid[wr][wc].is_open()
+                })
+                .collect();
+            let turns = [
+                facing.turn_right(),
+                facing,
+                facing.turn_left(),
+                facing.turn_left().turn_left(),
+            ];
+            let (dir, next) = turns
+                .iter()
+                .find_map(|&turn| open.iter().find(|&&(dir, _)| dir == turn).copied())?;
+
+            facing = dir;
+            current = next;
+            path.push(current);
+        }
+
+        Some(path)
+    }
+
+    /// How many times the shortest route from `start` to `end` changes direction, or
+    /// `None` if there isn't one. A straight drop down the cylinder has no turns.
+    pub fn total_turns(&self, start: (usize, usize), end: (usize, usize)) -> Option<usize> {
+        let path = self.solve_path(start, end)?;
+        let directions: Vec<Direction> = path
+            .windows(2)
+            .filter_map(|step| self.open_direction(step[0], step[1]))
+            .collect();
+        Some(directions.windows(2).filter(|d| d[0] != d[1]).count())
+    }
+
+    /// A single number for how hard the maze is between `start` and `end`, using the
+    /// default `ComplexityWeights`. Higher is harder, and it's 0 if there's no solution.
+    pub fn complexity_score(&self, start: (usize, usize), end: (usize, usize)) -> f64 {
+        self.complexity_score_weighted(start, end, &ComplexityWeights::default())
+    }
+
+    /// A single number for how hard the maze is between `start` and `end`: the sum of each
+    /// measure in `ComplexityWeights`, times its weight. It's 0 if there's no solution.
+    pub fn complexity_score_weighted(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+        weights: &ComplexityWeights,
+    ) -> f64 {
+        let Some(solution) = self.solve_path(start, end) else {
+            return 0.0;
+        };
+        let cells = (self.rows * self.cols) as f64;
+        let dead_ends = self.dead_ends();
+        let branch_points = self.branch_points().len();
+
+        // How far each dead end is from the solution, by searching out from all of it
+        let mut depth = vec![vec![None; self.cols]; self.rows];
+        let mut queue = VecDeque::new();
+        for &(row, col) in &solution {
+            depth[row][col] = Some(0);
+            queue.push_back((row, col));
+        }
+        while let Some((row, col)) = queue.pop_front() {
+            let d = depth[row][col].unwrap_or(0);
+            for (nr, nc) in self.open_neighbors(row, col) {
+                if depth[nr][nc].is_none() {
+                    depth[nr][nc] = Some(d + 1);
+                    queue.push_back((nr, nc));
+                }
+            }
+        }
+        let total_depth: usize = dead_ends.iter().filter_map(|&(r, c)| depth[r][c]).sum();
+        let branch_depth = if dead_ends.is_empty() {
+            0.0
+        } else {
+            total_depth as f64 / dead_ends.len() as f64 / (self.rows + self.cols) as f64
+        };
+
+        weights.solution_length * solution.len() as f64 / cells
+            + weights.dead_ends * dead_ends.len() as f64 / cells
+            + weights.branch_points * branch_points as f64 / cells
+            + weights.branch_depth * branch_depth
+    }
+
+    /// Logical path cells with exactly one open passage to a neighbor
+    pub fn dead_ends(&self) -> Vec<(usize, usize)> {
+        let mut dead_ends = Vec::new();
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let (gr, gc) = self.cell_to_grid(row, col);
+                if self.grid[gr][gc].is_open() && self.open_neighbors(row, col).len() == 1 {
+                    dead_ends.push((row, col));
+                }
+            }
+        }
+        dead_ends
+    }
+
+    /// Logical path cells with three or more open passages to neighbors, where the maze
+    /// branches
+    pub fn branch_points(&self) -> Vec<(usize, usize)> {
+        let mut branch_points = Vec::new();
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let (gr, gc) = self.cell_to_grid(row, col);
+                if self.grid[gr][gc].is_open() && self.open_neighbors(row, col).len() >= 3 {
+                    branch_points.push((row, col));
+                }
+            }
+        }
+        branch_points
+    }
+
+    /// The maze as a graph: every logical path cell, with the cells it has an open passage
+    /// to, including across the seam
+    pub fn to_graph(&self) -> HashMap<(usize, usize), Vec<(usize, usize)>> {
+        let mut graph = HashMap::new();
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let (gr, gc) = self.cell_to_grid(row, col);
+                if self.grid[gr][gc].is_open() {
+                    graph.insert((row, col), self.open_neighbors(row, col));
+                }
+            }
+        }
+        graph
+    }
+
+    /// Neighbors of a cell that are connected to it by an open wall, including across the
+    /// seam
+    fn open_neighbors(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.get_neighbors_with_dir(row, col)
+            .into_iter()
+            // With a single column, the seam would join a cell to itself
+            .filter(|&(_, neighbor)| neighbor != (row, col))
+            .filter(|&(dir, _)| {
+                let (wr, wc) = self.wall_toward(row, col, dir);
+                self.grid[wr][wc].is_open()
+            })
+            .map(|(_, neighbor)| neighbor)
+            .collect()
+    }
+
+    /// The direction of an open passage from cell `from` to its neighbor `to`, or `None`
+    /// if they aren't connected. With two columns a cell's left and right neighbor are the
+    /// same cell, so this picks whichever way is open.
+    fn open_direction(&self, from: (usize, usize), to: (usize, usize)) -> Option<Direction> {
+        self.get_neighbors_with_dir(from.0, from.1)
+            .into_iter()
+            .filter(|&(_, neighbor)| neighbor == to)
+            .map(|(dir, _)| dir)
+            .find(|&dir| {
+                let (wr, wc) = self.wall_toward(from.0, from.1, dir);
+                self.grid[wr][wc].is_open()
+            })
+    }
+
+    /// The grid square of the wall on one side of a cell, which is the seam on the outer
+    /// side of the first and last columns
+    fn wall_toward(&self, row: usize, col: usize, dir: Direction) -> (usize, usize) {
+        let (gr, gc) = self.cell_to_grid(row, col);
+        match dir {
+            Direction::Up => (gr - 1, gc),
+            Direction::Down => (gr + 1, gc),
+            Direction::Left if col == 0 => (gr, 0),
+            Direction::Left => (gr, gc - 1),
+            Direction::Right if col == self.cols - 1 => (gr, self.grid.width() - 1),
+            Direction::Right => (gr, gc + 1),
+        }
+    }
+
+    /// Check that `start` and `end` are cells in the maze. With `require_top_bottom`, also
+    /// check that the start is in the top row and the end in the bottom row, where the
+    /// generators open the cylinder's ends.
+    pub fn validate_endpoints(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+        require_top_bottom: bool,
+    ) -> Result<()> {
+        for (name, (row, col)) in [("Start", start), ("End", end)] {
+            if row >= self.rows || col >= self.cols {
+                bail!(
+                    "{name} ({row}, {col}) is outside the {}x{} maze",
+                    self.rows,
+                    self.cols
+                );
+            }
+        }
+        if require_top_bottom {
+            if start.0 != 0 {
+                bail!("Start {start:?} is not in the top row");
+            }
+            if end.0 != self.rows - 1 {
+                bail!("End {end:?} is not in the bottom row {}", self.rows - 1);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The grid square of a logical cell
+    pub fn cell_to_grid(&self, row: usize, col: usize) -> (usize, usize) {
+        (2 * row + 1, 2 * col + 1)
+    }
+
+    /// The logical cell at a grid square, or `None` if the square is a wall between cells
+    /// or outside the grid
+    pub fn grid_to_cell(&self, grid_row: usize, grid_col: usize) -> Option<(usize, usize)> {
+        if grid_row % 2 == 1 && grid_col % 2 == 1 {
+            let (row, col) = (grid_row / 2, grid_col / 2);
+            (row < self.rows && col < self.cols).then_some((row, col))
+        } else {
+            None
+        }
+    }
+
+    fn get_neighbors(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.get_neighbors_with_dir(row, col)
+            .into_iter()
+            .map(|(_, neighbor)| neighbor)
+            .collect()
+    }
+
+    /// The cells next to a cell, whether or not there's a wall between them, along with
+    /// the direction of each one
+    pub fn get_neighbors_with_dir(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> Vec<(Direction, (usize, usize))> {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|dir| Some((dir, dir.apply(self, (row, col))?)))
+            .collect()
+    }
+
+    /// Pick a neighbor of `cell` within `rows` at random, weighting the ones above and
+    /// below by `bias`
+    fn random_neighbor(
+        &self,
+        cell: (usize, usize),
+        rows: &Range<usize>,
+        bias: VerticalBias,
+    ) -> (usize, usize) {
+        let mut neighbors = self.get_neighbors(cell.0, cell.1);
+        neighbors.retain(|n| rows.contains(&n.0));
+        let weight = |n: &(usize, usize)| if n.0 == cell.0 { 1.0 } else { bias.0 };
+
+        let mut choice = self.rng.unit() * neighbors.iter().map(weight).sum::<f64>();
+        for neighbor in &neighbors {
+            choice -= weight(neighbor);
+            if choice < 0.0 {
+                return *neighbor;
+            }
+        }
+        // Rounding can leave a tiny remainder after the last neighbor
+        neighbors[neighbors.len() - 1]
+    }
+
+    fn carve_passage(&mut self, from: (usize, usize), to: (usize, usize)) {
+        let (from_r, from_c) = self.cell_to_grid(from.0, from.1);
+        let (to_r, to_c) = self.cell_to_grid(to.0, to.1);
+
+        // Mark both cells as paths
+        self.grid[from_r][from_c] = Cell::Path;
+        self.grid[to_r][to_c] = Cell::Path;
+
+        // Mark the wall between them as path
+        // Handle wrapping for horizontal movement
+        if from.0 == to.0 {
+            // Horizontal movement
+            if (from.1 == 0 && to.1 == self.cols - 1) || (from.1 == self.cols - 1 && to.1 == 0) {
+                // Wrapping around cylinder - connect through leftmost and rightmost walls
+                // The leftmost wall (column 0) and rightmost wall (column grid_cols-1) are the same
+                let grid_cols = self.grid[0].len();
+                self.grid[from_r][0] = Cell::Path;
+                self.grid[from_r][grid_cols - 1] = Cell::Path;
+            } else {
+                let wall_c = (from_c + to_c) / 2;
+                self.grid[from_r][wall_c] = Cell::Path;
+            }
+        } else {
+            // Vertical movement
+            let wall_r = (from_r + to_r) / 2;
+            self.grid[wall_r][from_c] = Cell::Path;
+        }
+    }
+
+    pub fn generate_wilson(&mut self) -> ((usize, usize), (usize, usize)) {
+        self.generate_wilson_with(VerticalBias::default(), |_| {})
+    }
+
+    /// Generate the maze with Wilson's algorithm, with the random walks favoring vertical
+    /// steps by `bias`
+    pub fn generate_wilson_biased(
+        &mut self,
+        bias: VerticalBias,
+    ) -> ((usize, usize), (usize, usize)) {
+        self.generate_wilson_with(bias, |_| {})
+    }
+
+    /// Generate the maze with Wilson's algorithm, calling `on_carve` with the grid after
+    /// every step that carves out part of the maze
+    pub fn generate_wilson_with(
+        &mut self,
+        bias: VerticalBias,
+        on_carve: impl FnMut(&Grid),
+    ) -> ((usize, usize), (usize, usize)) {
+        assert!(
+            bias.0 > 0.0,
+            "Vertical bias must be positive, not {}",
+            bias.0
+        );
+        let (start, end) = self.random_endpoints();
+        self.wilson(start, end, bias, on_carve);
+        self.set_endpoints(start, end);
+        (start, end)
+    }
+
+    /// Generate the maze with Wilson's algorithm, calling `progress` with the fraction of
+    /// cells added to the maze so far each time one is added, ending with 1.0
+    pub fn generate_wilson_with_progress(
+        &mut self,
+        bias: VerticalBias,
+        mut progress: impl FnMut(f32),
+    ) -> ((usize, usize), (usize, usize)) {
+        // The first cell and each carve add one cell, then the endpoints are opened
+        let cells = (self.rows * self.cols) as f32;
+        let mut added = 0.0;
+        self.generate_wilson_with(bias, |_| {
+            added += 1.0;
+            progress(f32::min(added / cells, 1.0));
+        })
+    }
+
+    /// Generate the maze with Wilson's algorithm, between any two cells. Endpoints in the
+    /// top or bottom row are opened to the top or bottom edge of the cylinder, so both can
+    /// be in the top row to enter and leave from the same end.
+    pub fn generate_wilson_between(&mut self, start: (usize, usize), end: (usize, usize)) {
+        assert!(
+            start.0 < self.rows && start.1 < self.cols && end.0 < self.rows && end.1 < self.cols,
+            "Start {start:?} and end {end:?} must be inside the {}x{} maze",
+            self.rows,
+            self.cols
+        );
+        self.wilson(start, end, VerticalBias::default(), |_| {});
+        self.set_endpoints(start, end);
+    }
+
+    /// Generate a maze with Wilson's algorithm in just the cells in `rows`, without
+    /// touching the rest of the grid, and return a start in its top row and an end in its
+    /// bottom row. Regions generated in separate rows are separate mazes, with solid walls
+    /// between them. The outer wall is only opened next to the start or end if it's in
+    /// the top or bottom row of the whole cylinder.
+    pub fn generate_region(&mut self, rows: Range<usize>) -> ((usize, usize), (usize, usize)) {
+        assert!(
+            !rows.is_empty() && rows.end <= self.rows,
+            "Region {rows:?} must be inside the {} rows of the maze",
+            self.rows
+        );
+        let start = (rows.start, self.rng.below(self.cols));
+        let end = (rows.end - 1, self.rng.below(self.cols));
+        self.wilson_rows(rows, &[start], VerticalBias::default(), &mut |_| {});
+        if start.0 == 0 {
+            self.open_endpoint(start, false);
+        }
+        if end.0 == self.rows - 1 {
+            self.open_endpoint(end, true);
+        }
+        self.set_endpoints(start, end);
+        (start, end)
+    }
+
+    /// Generate mazes with Wilson's algorithm until one has a solution at least
+    /// `min_length` steps long, with the end moved to the farthest bottom cell from the
+    /// start. Returns the endpoints and the length of the solution, or `None` if no maze
+    /// was long enough after `MAX_ATTEMPTS` tries, leaving the last one generated.
+    pub fn generate_long_solution(&mut self, min_length: usize) -> Option<(Endpoints, usize)> {
+        const MAX_ATTEMPTS: usize = 1000;
+
+        for _ in 0..MAX_ATTEMPTS {
+            self.reset();
+            let (start, end) = self.generate_wilson();
+
+            // No two cells are far enough apart, so don't bother looking for endpoints
+            if self.longest_path().0 < min_length {
+                continue;
+            }
+
+            let end = self.move_end_farthest(start, end);
+            let length = self.distances_from(start)[end.0][end.1].unwrap_or(0);
+            if length >= min_length {
+                return Some(((start, end), length));
+            }
+        }
+        None
+    }
+
+    /// A random cell in the top row to start from, and one in the bottom row to end at
+    fn random_endpoints(&self) -> ((usize, usize), (usize, usize)) {
+        let start = (0, self.rng.below(self.cols));
+        let end = (self.rows - 1, self.rng.below(self.cols));
+        (start, end)
+    }
+
+    fn wilson(
+        &mut self,
+        start: (usize, usize),
+        end: (usize, usize),
+        bias: VerticalBias,
+        mut on_carve: impl FnMut(&Grid),
+    ) {
+        self.wilson_rows(0..self.rows, &[start], bias, &mut on_carve);
+        self.open_endpoint(start, false);
+        self.open_endpoint(end, true);
+        on_carve(&self.grid);
+    }
+
+    /// Carve a spanning tree of the cells in `rows` with Wilson's algorithm, growing it
+    /// from the cells in `tree`, which must already be connected to each other
+    fn wilson_rows(
+        &mut self,
+        rows: Range<usize>,
+        tree: &[(usize, usize)],
+        bias: VerticalBias,
+        on_carve: &mut impl FnMut(&Grid),
+    ) {
+        let mut in_maze = HashSet::new();
+
+        // Start with the tree
+        for &(row, col) in tree {
+            in_maze.insert((row, col));
+            let (gr, gc) = self.cell_to_grid(row, col);
+            self.grid[gr][gc] = Cell::Path;
+        }
+        on_carve(&self.grid);
+
+        // Add all other cells
+        for row in rows.clone() {
+            for col in 0..self.cols {
+                if in_maze.contains(&(row, col)) {
+                    continue;
+                }
+
+                // Perform loop-erased random walk
+                let mut path = vec![(row, col)];
+                let mut current = (row, col);
+
+                while !in_maze.contains(&current) {
+                    let next = self.random_neighbor(current, &rows, bias);
+
+                    // Check if we've visited this cell in current walk
+                    if let Some(pos) = path.iter().position(|&p| p == next) {
+                        // Loop detected - erase the loop
+                        path.truncate(pos + 1);
+                    } else {
+                        path.push(next);
+                    }
+
+                    current = next;
+                }
+
+                // Add the path to the maze by carving passages
+                for i in 0..path.len() {
+                    let cell = path[i];
+                    in_maze.insert(cell);
+
+                    if i > 0 {
+                        self.carve_passage(path[i - 1], cell);
+                        on_carve(&self.grid);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generate a maze whose solution is a helix, winding `turns` times around the cylinder
+    /// on its way from the top to the bottom. The rest of the maze is filled in with
+    /// Wilson's algorithm, as dead ends branching off the helix. A row can hold at most
+    /// one turn, so the helix is flatter than asked for if `turns` is more than `rows`.
+    pub fn generate_spiral(&mut self, turns: f64) -> ((usize, usize), (usize, usize)) {
+        assert!(
+            turns.is_finite() && turns >= 0.0,
+            "Spiral turns must be finite and not negative, not {turns}"
+        );
+        let start = (0, self.rng.below(self.cols));
+
+        // Spread the steps around the cylinder evenly over the rows
+        let steps = turns * self.cols as f64;
+        let rows = self.rows as f64;
+        let steps_before = |row: usize| (steps * row as f64 / rows).round() as usize;
+        let mut helix = vec![start];
+        let mut current = start;
+        for row in 0..self.rows {
+            let across = (steps_before(row + 1) - steps_before(row)).min(self.cols - 1);
+            for _ in 0..across {
+                let next = (row, (current.1 + 1) % self.cols);
+                self.carve_passage(current, next);
+                helix.push(next);
+                current = next;
+            }
+            if row + 1 < self.rows {
+                let next = (row + 1, current.1);
+                self.carve_passage(current, next);
+                helix.push(next);
+                current = next;
+            }
+        }
+        let end = current;
+
+        self.wilson_rows(0..self.rows, &helix, VerticalBias::default(), &mut |_| {});
+        self.open_endpoint(start, false);
+        self.open_endpoint(end, true);
+        self.set_endpoints(start, end);
+        (start, end)
+    }
+
+    /// Generate a maze that is the same when turned upside down. The top half is generated
+    /// with Wilson's algorithm and mirrored onto the bottom half. With an odd number of
+    /// rows, the middle row is a single corridor. The halves are joined through one gap
+    /// in the middle, and the end is directly below the start.
+    pub fn generate_symmetric(&mut self) -> ((usize, usize), (usize, usize)) {
+        let half = self.rows / 2;
+        let start = (0, self.rng.below(self.cols));
+        let end = (self.rows - 1, start.1);
+
+        if half > 0 {
+            self.wilson_rows(0..half, &[start], VerticalBias::default(), &mut |_| {});
+        }
+
+        // Mirror the top half of the grid onto the bottom half
+        let grid_rows = self.grid.len();
+        for r in 0..2 * half {
+            self.grid.copy_row(r, grid_rows - 1 - r);
+        }
+
+        // Join the halves in the middle, through the middle row if there is one
+        let gap_col = self.rng.below(self.cols);
+        if self.rows % 2 == 1 {
+            let cut_col = self.rng.below(self.cols);
+            for col in 0..self.cols {
+                let (gr, gc) = self.cell_to_grid(half, col);
+                self.grid[gr][gc] = Cell::Path;
+                if col != cut_col {
+                    self.set_right_wall(half, col, Cell::Path);
+                }
+            }
+            if half > 0 {
+                self.set_bottom_wall(half - 1, gap_col, Cell::Path);
+                self.set_bottom_wall(half, gap_col, Cell::Path);
+            }
+        } else {
+            self.set_bottom_wall(half - 1, gap_col, Cell::Path);
+        }
+
+        self.open_endpoint(start, false);
+        self.open_endpoint(end, true);
+        self.set_endpoints(start, end);
+        (start, end)
+    }
+
+    /// Generate the maze by recursive division: start with every cell open, then split it
+    /// into chambers with walls that each have a single gap. While the whole cylinder is
+    /// still one ring, horizontal walls go all the way around it. The ring is cut open
+    /// with one full-height wall before it's divided vertically.
+    pub fn generate_recursive_division(&mut self) -> ((usize, usize), (usize, usize)) {
+        let bottom_row = self.grid.len() - 1;
+
+        // Open everything except the top and bottom edges and the posts between walls
+        for (r, row) in self.grid.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                let is_post = r % 2 == 0 && c % 2 == 0;
+                *cell = if r == 0 || r == bottom_row || is_post {
+                    Cell::Wall
+                } else {
+                    Cell::Path
+                };
+            }
+        }
+
+        // Regions are (top row, height, left column, width, is a ring). Columns of a
+        // region wrap around the seam.
+        let mut regions = vec![(0, self.rows, 0, self.cols, true)];
+        while let Some((top, height, left, width, ring)) = regions.pop() {
+            if ring {
+                let split = height > 1 && self.rng.chance(height as f64 / (height + width) as f64);
+                if split {
+                    // A horizontal wall all the way around, with one gap
+                    let wall_row = top + self.rng.below(height - 1);
+                    let gap = self.rng.below(self.cols);
+                    for col in (0..self.cols).filter(|&col| col != gap) {
+                        self.set_bottom_wall(wall_row, col, Cell::Wall);
+                    }
+                    let top_height = wall_row - top + 1;
+                    regions.push((top, top_height, 0, width, true));
+                    regions.push((wall_row + 1, height - top_height, 0, width, true));
+                } else {
+                    // Cut the ring open into a rectangle, starting just after the cut
+                    let cut_col = self.rng.below(self.cols);
+                    for row in top..top + height {
+                        self.set_right_wall(row, cut_col, Cell::Wall);
+                    }
+                    regions.push((top, height, (cut_col + 1) % self.cols, width, false));
+                }
+                continue;
+            }
+
+            if height < 2 || width < 2 {
+                continue;
+            }
+
+            let horizontal = if height == width {
+                self.rng.chance(0.5)
+            } else {
+                height > width
+            };
+            if horizontal {
+                let wall_row = top + self.rng.below(height - 1);
+                let gap = self.rng.below(width);
+                for i in (0..width).filter(|&i| i != gap) {
+                    self.set_bottom_wall(wall_row, (left + i) % self.cols, Cell::Wall);
+                }
+                let top_height = wall_row - top + 1;
+                regions.push((top, top_height, left, width, false));
+                regions.push((wall_row + 1, height - top_height, left, width, false));
+            } else {
+                let left_width = self.rng.in_range(1..width);
+                let wall_col = (left + left_width - 1) % self.cols;
+                let gap = top + self.rng.below(height);
+                for row in (top..top + height).filter(|&row| row != gap) {
+                    self.set_right_wall(row, wall_col, Cell::Wall);
+                }
+                regions.push((top, height, left, left_width, false));
+                regions.push((
+                    top,
+                    height,
+                    (wall_col + 1) % self.cols,
+                    width - left_width,
+                    false,
+                ));
+            }
+        }
+
+        let (start, end) = self.random_endpoints();
+        self.open_endpoint(start, false);
+        self.open_endpoint(end, true);
+        self.set_endpoints(start, end);
+        (start, end)
+    }
+
+    /// Open up a `fraction` of the walls between neighboring cells that are still closed,
+    /// chosen at random. At 0.0 the maze is left as it is, and at 1.0 it's one open
+    /// chamber. This only ever adds passages, so a solvable maze stays solvable, but
+    /// it does make loops.
+    pub fn open_fraction(&mut self, fraction: f64) {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "Fraction of walls to open must be between 0 and 1, not {fraction}"
+        );
+
+        // Each closed wall as the cell it's below or right of, and whether it's below
+        let mut walls = Vec::new();
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                // Each wall is looked at directly, since with two columns the cells in a
+                // row are joined by two walls, and either can be closed
+                let (wr, wc) = self.wall_toward(row, col, Direction::Down);
+                if row + 1 < self.rows && !self.grid[wr][wc].is_open() {
+                    walls.push((row, col, true));
+                }
+                let (wr, wc) = self.wall_toward(row, col, Direction::Right);
+                if self.cols > 1 && !self.grid[wr][wc].is_open() {
+                    walls.push((row, col, false));
+                }
+            }
+        }
+
+        // A partial shuffle puts the walls to open first
+        let count = (fraction * walls.len() as f64).round() as usize;
+        if count > 0 {
+            self.solution.take();
+        }
+        for i in 0..count {
+            let j = self.rng.in_range(i..walls.len());
+            walls.swap(i, j);
+            let (row, col, below) = walls[i];
+            let (gr, gc) = self.cell_to_grid(row, col);
+            self.grid[gr][gc] = Cell::Path;
+            if below {
+                self.set_bottom_wall(row, col, Cell::Path);
+                let (gr, gc) = self.cell_to_grid(row + 1, col);
+                self.grid[gr][gc] = Cell::Path;
+            } else {
+                self.set_right_wall(row, col, Cell::Path);
+                let (gr, gc) = self.cell_to_grid(row, (col + 1) % self.cols);
+                self.grid[gr][gc] = Cell::Path;
+            }
+        }
+    }
+
+    /// Set the wall between a cell and the one below it
+    fn set_bottom_wall(&mut self, row: usize, col: usize, cell: Cell) {
+        let (gr, gc) = self.cell_to_grid(row, col);
+        self.grid[gr + 1][gc] = cell;
+    }
+
+    /// Set the wall between a cell and the one to its right, which is the seam for the
+    /// rightmost column
+    fn set_right_wall(&mut self, row: usize, col: usize, cell: Cell) {
+        let (gr, gc) = self.cell_to_grid(row, col);
+        if col == self.cols - 1 {
+            let grid_cols = self.grid[0].len();
+            self.grid[gr][0] = cell;
+            self.grid[gr][grid_cols - 1] = cell;
+        } else {
+            self.grid[gr][gc + 1] = cell;
+        }
+    }
+
+    /// Open the outer wall next to an endpoint, if it's in the top or bottom row. A maze
+    /// with a single row could open either, so `bottom_first` picks which.
+    fn open_endpoint(&mut self, cell: (usize, usize), bottom_first: bool) {
+        let (_, grid_col) = self.cell_to_grid(cell.0, cell.1);
+        let bottom_row = self.grid.len() - 1;
+        let at_top = cell.0 == 0;
+        let at_bottom = cell.0 == self.rows - 1;
+
+        if at_bottom && (bottom_first || !at_top) {
+            self.grid[bottom_row][grid_col] = Cell::Path;
+        } else if at_top {
+            self.grid[0][grid_col] = Cell::Path;
+        }
+    }
+
+    pub fn display(&self, start: (usize, usize), end: (usize, usize)) {
+        self.display_styled(start, end, &DisplayStyle::default());
+    }
+
+    /// Print the full grid with the maze's own start and end marked, or without markers
+    /// if it doesn't have them yet
+    pub fn show(&self) {
+        for line in self.render_grid(self.endpoints, &DisplayStyle::default()) {
+            println!("{line}");
+        }
+    }
+
+    /// Print the full grid, drawing each kind of square with the characters from `style`
+    pub fn display_styled(&self, start: (usize, usize), end: (usize, usize), style: &DisplayStyle) {
+        for line in self.render_rows_styled(start, end, style) {
+            println!("{line}");
+        }
+    }
+
+    /// The full grid as text, one string per grid row, as `display` prints it
+    pub fn render_rows(&self, start: (usize, usize), end: (usize, usize)) -> Vec<String> {
+        self.render_rows_styled(start, end, &DisplayStyle::default())
+    }
+
+    /// The full grid as text, one string per grid row, drawing each kind of square with
+    /// the characters from `style`
+    pub fn render_rows_styled(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+        style: &DisplayStyle,
+    ) -> Vec<String> {
+        self.render_grid(Some((start, end)), style)
+    }
+
+    /// The full grid as text, with `endpoints` marked if there are any
+    fn render_grid(&self, endpoints: Option<Endpoints>, style: &DisplayStyle) -> Vec<String> {
+        let to_grid = |(row, col): (usize, usize)| self.cell_to_grid(row, col);
+        let start = endpoints.map(|(start, _)| to_grid(start));
+        let end = endpoints.map(|(_, end)| to_grid(end));
+
+        let mut lines = Vec::new();
+        for (r, row) in self.grid.iter().enumerate() {
+            let mut line = String::new();
+            let repeat = style.repeat_seam.then_some(1);
+            for c in (0..row.len()).chain(repeat) {
+                if start == Some((r, c)) {
+                    line.push(style.start);
+                } else if end == Some((r, c)) {
+                    line.push(style.end);
+                } else {
+                    match row[c] {
+                        Cell::Wall => line.push(style.wall),
+                        Cell::Path => line.push(style.path),
+                        Cell::Solution => line.push(style.solution),
+                    }
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Print the maze with one character per logical cell, using `+`, `-` and `|`
+    /// for the walls between cells
+    pub fn display_compact(&self, start: (usize, usize), end: (usize, usize)) {
+        for line in self.render_compact(start, end) {
+            println!("{line}");
+        }
+    }
+
+    /// The maze as text, one string per line, as `display_compact` prints it
+    pub fn render_compact(&self, start: (usize, usize), end: (usize, usize)) -> Vec<String> {
+        let mut lines = Vec::new();
+        for row in 0..self.rows {
+            let (gr, _) = self.cell_to_grid(row, 0);
+
+            // Walls above this row of cells
+            let mut line = String::from("+");
+            for col in 0..self.cols {
+                let (_, gc) = self.cell_to_grid(row, col);
+                line.push(self.wall_glyph(gr - 1, gc, '-'));
+                line.push('+');
+            }
+            lines.push(line);
+
+            // The cells themselves, with the walls between them. The leftmost wall is the
+            // seam, and is the same wall as the rightmost one.
+            let mut line = String::new();
+            line.push(self.wall_glyph(gr, 0, '|'));
+            for col in 0..self.cols {
+                let (_, gc) = self.cell_to_grid(row, col);
+                if (row, col) == start {
+                    line.push('S');
+                } else if (row, col) == end {
+                    line.push('E');
+                } else if self.grid[gr][gc] == Cell::Solution {
+                    line.push('.');
+                } else {
+                    line.push(' ');
+                }
+                line.push(self.wall_glyph(gr, gc + 1, '|'));
+            }
+            lines.push(line);
+        }
+
+        // Walls below the bottom row
+        let bottom_row = self.grid.len() - 1;
+        let mut line = String::from("+");
+        for col in 0..self.cols {
+            let (_, gc) = self.cell_to_grid(self.rows - 1, col);
+            line.push(self.wall_glyph(bottom_row, gc, '-'));
+            line.push('+');
+        }
+        lines.push(line);
+        lines
+    }
+
+    /// The character to draw for a grid position in the compact display: `glyph` for a wall,
+    /// and a space for a path
+    fn wall_glyph(&self, r: usize, c: usize, glyph: char) -> char {
+        match self.grid[r][c] {
+            Cell::Wall => glyph,
+            Cell::Path | Cell::Solution => ' ',
+        }
+    }
+
+    pub fn can_solve(&self, start: (usize, usize), end: (usize, usize)) -> bool {
+        let (start_r, start_c) = self.cell_to_grid(start.0, start.1);
+        let (end_r, end_c) = self.cell_to_grid(end.0, end.1);
+
+        let mut queue = VecDeque::new();
+        let mut visited = HashSet::new();
+
+        queue.push_back((start_r, start_c));
+        visited.insert((start_r, start_c));
+
+        let grid_rows = self.grid.len();
+        let grid_cols = self.grid[0].len();
+
+        while let Some((r, c)) = queue.pop_front() {
+            if (r, c) == (end_r, end_c) {
+                return true;
+            }
+
+            // Check all four directions
+            let mut neighbors = Vec::new();
+
+            // Up
+            if r > 0 {
+                neighbors.push((r - 1, c));
+            }
+            // Down
+            if r + 1 < grid_rows {
+                neighbors.push((r + 1, c));
+            }
+            // Left (with wrapping)
+            let left_c = if c == 0 { grid_cols - 1 } else { c - 1 };
+            neighbors.push((r, left_c));
+
+            // Right (with wrapping)
+            let right_c = (c + 1) % grid_cols;
+            neighbors.push((r, right_c));
+
+            for (nr, nc) in neighbors {
+                if !visited.contains(&(nr, nc)) && self.grid[nr][nc].is_open() {
+                    visited.insert((nr, nc));
+                    queue.push_back((nr, nc));
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maze_is_solvable() {
+        // Generate multiple mazes and verify they're all solvable
+        for _ in 0..10 {
+            let mut maze = CylinderMaze::new(10, 10);
+            let (start, end) = maze.generate_wilson();
+
+            assert!(
+                maze.can_solve(start, end),
+                "Maze should be solvable from S to E"
+            );
+        }
+    }
+
+    #[test]
+    fn test_small_maze_solvable() {
+        let mut maze = CylinderMaze::new(3, 3);
+        let (start, end) = maze.generate_wilson();
+
+        assert!(
             maze.can_solve(start, end),
             "Small maze should be solvable from S to E"
         );
     }
 
     #[test]
-    fn test_large_maze_solvable() {
-        let mut maze = CylinderMaze::new(50, 50);
+    fn test_large_maze_solvable() {
+        let mut maze = CylinderMaze::new(50, 50);
+        let (start, end) = maze.generate_wilson();
+
+        assert!(
+            maze.can_solve(start, end),
+            "Large maze should be solvable from S to E"
+        );
+    }
+
+    #[test]
+    fn test_generate_wilson_with() {
+        let mut snapshots: Vec<GridSnapshot> = Vec::new();
+        let mut maze = CylinderMaze::new(5, 5);
+        maze.generate_wilson_with(VerticalBias::default(), |grid| {
+            snapshots.push(grid.to_vec())
+        });
+
+        // One snapshot for the first cell, one per passage, and one for the openings
+        assert_eq!(snapshots.len(), 5 * 5 + 1);
+        assert_eq!(*maze.grid(), *snapshots.last().unwrap());
+    }
+
+    #[test]
+    fn test_generate_wilson_with_progress() {
+        let mut fractions = Vec::new();
+        let mut maze = CylinderMaze::new(4, 5);
+        maze.generate_wilson_with_progress(VerticalBias::default(), |f| fractions.push(f));
+
+        assert_eq!(fractions.len(), 4 * 5 + 1);
+        assert_eq!(fractions[0], 1.0 / 20.0);
+        assert!(fractions.windows(2).all(|f| f[0] <= f[1]));
+        assert_eq!(fractions.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn test_recursive_division_solvable() {
+        for (rows, cols) in [(1, 1), (1, 5), (5, 1), (2, 2), (10, 10), (30, 8), (8, 30)] {
+            let mut maze = CylinderMaze::new(rows, cols);
+            let (start, end) = maze.generate_recursive_division();
+
+            assert!(
+                maze.can_solve(start, end),
+                "Recursive division maze ({rows}x{cols}) should be solvable from S to E"
+            );
+        }
+    }
+
+    #[test]
+    fn test_vertical_bias_solvable() {
+        for bias in [0.1, 1.0, 10.0] {
+            let mut maze = CylinderMaze::new(15, 15);
+            let (start, end) = maze.generate_wilson_biased(VerticalBias(bias));
+
+            assert!(
+                maze.can_solve(start, end),
+                "Maze with vertical bias {bias} should be solvable from S to E"
+            );
+        }
+    }
+
+    #[test]
+    fn test_symmetric() {
+        for (rows, cols) in [(1, 1), (1, 4), (2, 3), (7, 7), (10, 12)] {
+            let mut maze = CylinderMaze::new(rows, cols);
+            let (start, end) = maze.generate_symmetric();
+
+            assert!(
+                maze.can_solve(start, end),
+                "Symmetric maze ({rows}x{cols}) should be solvable from S to E"
+            );
+            assert_eq!(maze.passage_count(), rows * cols - 1);
+            let mut flipped = maze.grid().to_vec();
+            flipped.reverse();
+            assert_eq!(*maze.grid(), flipped, "Maze should be symmetric");
+        }
+    }
+
+    #[test]
+    fn test_generate_spiral() {
+        let mut maze = CylinderMaze::new(5, 8);
+        let (start, end) = maze.generate_spiral(2.0);
+        assert_eq!(maze.passage_count(), 5 * 8 - 1);
+
+        // The solution goes twice around, and straight down between rows
+        let solution = maze.solve_path(start, end).unwrap();
+        let across = solution.windows(2).filter(|s| s[0].0 == s[1].0).count();
+        assert_eq!(across, 16);
+        assert_eq!(solution.len(), 16 + 5);
+
+        // Too many turns for the rows goes as far round as it can
+        let mut maze = CylinderMaze::new(3, 4);
+        let (start, end) = maze.generate_spiral(10.0);
+        assert_eq!(maze.solve_path(start, end).unwrap().len(), 3 * 4);
+    }
+
+    #[test]
+    fn test_generate_region() {
+        let mut maze = CylinderMaze::new(8, 6);
+        let (top_start, top_end) = maze.generate_region(0..3);
+        let (bottom_start, bottom_end) = maze.generate_region(4..8);
+        assert_eq!((top_start.0, top_end.0), (0, 2));
+        assert_eq!((bottom_start.0, bottom_end.0), (4, 7));
+
+        assert!(maze.can_solve(top_start, top_end));
+        assert!(maze.can_solve(bottom_start, bottom_end));
+        assert!(!maze.can_solve(top_start, bottom_end));
+        assert_eq!(maze.passage_count(), (3 * 6 - 1) + (4 * 6 - 1));
+        assert!((0..6).all(|col| maze.cell(3, col) == Some(Cell::Wall)));
+    }
+
+    #[test]
+    fn test_generate_long_solution() {
+        let mut maze = CylinderMaze::new(6, 6);
+        let ((start, end), length) = maze.generate_long_solution(20).unwrap();
+        assert!(length >= 20);
+        assert_eq!(maze.solve_path(start, end).unwrap().len(), length + 1);
+        assert_eq!((start.0, end.0), (0, 5));
+        assert_eq!(maze.passage_count(), 6 * 6 - 1);
+
+        // Longer than any route through the maze can be
+        let mut maze = CylinderMaze::new(2, 2);
+        assert_eq!(maze.generate_long_solution(4), None);
+    }
+
+    #[test]
+    fn test_generate_wilson_between() {
+        // Enter and leave from the top, on opposite sides of the cylinder
+        let mut maze = CylinderMaze::new(6, 8);
+        let (start, end) = ((0, 0), (0, 4));
+        maze.generate_wilson_between(start, end);
+
+        assert!(maze.can_solve(start, end));
+        assert_eq!(maze.grid()[0][1], Cell::Path, "Start is open to the top");
+        assert_eq!(maze.grid()[0][9], Cell::Path, "End is open to the top");
+    }
+
+    #[test]
+    fn test_mazes_are_trees() {
+        let sizes = [(1, 1), (1, 2), (2, 1), (2, 2), (3, 5), (7, 4), (12, 12)];
+        for (rows, cols) in sizes {
+            for _ in 0..20 {
+                let mut maze = CylinderMaze::new(rows, cols);
+                maze.generate_wilson();
+                assert_eq!(
+                    maze.passage_count(),
+                    rows * cols - 1,
+                    "Wilson maze ({rows}x{cols}) should be a tree"
+                );
+
+                let mut maze = CylinderMaze::new(rows, cols);
+                maze.generate_wilson_biased(VerticalBias(5.0));
+                assert_eq!(
+                    maze.passage_count(),
+                    rows * cols - 1,
+                    "Biased Wilson maze ({rows}x{cols}) should be a tree"
+                );
+
+                let mut maze = CylinderMaze::new(rows, cols);
+                maze.generate_recursive_division();
+                assert_eq!(
+                    maze.passage_count(),
+                    rows * cols - 1,
+                    "Recursive division maze ({rows}x{cols}) should be a tree"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_seam_columns_match() {
+        type Generator = fn(&mut CylinderMaze) -> ((usize, usize), (usize, usize));
+        let generators: [Generator; 3] = [
+            CylinderMaze::generate_wilson,
+            CylinderMaze::generate_recursive_division,
+            CylinderMaze::generate_symmetric,
+        ];
+        for generate in generators {
+            for (rows, cols) in [(1, 1), (2, 2), (5, 3), (8, 12)] {
+                let mut maze = CylinderMaze::new(rows, cols);
+                generate(&mut maze);
+                let last = maze.grid()[0].len() - 1;
+                for row in maze.grid() {
+                    assert_eq!(
+                        row[0], row[last],
+                        "Seam columns differ in {rows}x{cols} maze"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_edges_closed_except_endpoints() {
+        let mut wilson = CylinderMaze::new(6, 9);
+        let wilson_endpoints = wilson.generate_wilson();
+        let mut division = CylinderMaze::new(6, 9);
+        let division_endpoints = division.generate_recursive_division();
+
+        for (maze, (start, end)) in [(wilson, wilson_endpoints), (division, division_endpoints)] {
+            let grid = maze.grid();
+            let bottom_row = grid.len() - 1;
+            for (c, (top, bottom)) in grid[0].iter().zip(&grid[bottom_row]).enumerate() {
+                let top_open = c == 2 * start.1 + 1;
+                let bottom_open = c == 2 * end.1 + 1;
+                assert_eq!(*top == Cell::Path, top_open, "Top edge at {c}");
+                assert_eq!(*bottom == Cell::Path, bottom_open, "Bottom edge at {c}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_flat_grid() {
+        let mut maze = CylinderMaze::new(2, 3);
+        maze.carve_passage((0, 0), (0, 2));
+
+        let flat = maze.to_flat_grid(false);
+        assert_eq!(flat.len(), 2);
+        assert_eq!(flat[0], vec![Cell::Path, Cell::Wall, Cell::Path]);
+        assert_eq!(flat[1], vec![Cell::Wall; 3]);
+
+        let flat = maze.to_flat_grid(true);
+        assert_eq!(
+            flat[0],
+            vec![Cell::Path, Cell::Wall, Cell::Path, Cell::Path]
+        );
+        assert_eq!(flat[1], vec![Cell::Wall; 4]);
+    }
+
+    #[test]
+    fn test_wall_mask() {
+        let mut maze = CylinderMaze::new(1, 3);
+        maze.carve_passage((0, 0), (0, 1));
+        let mask = maze.wall_mask();
+        assert_eq!(mask.len(), 3);
+        assert!(mask.iter().all(|row| row.len() == 7));
+        assert_eq!(mask[1], vec![true, false, false, false, true, true, true]);
+        assert!(mask[0].iter().chain(&mask[2]).all(|&wall| wall));
+    }
+
+    #[test]
+    fn test_has_valid_shape() {
+        let mut maze = CylinderMaze::new(4, 7);
+        assert!(maze.has_valid_shape());
+
+        maze.cols += 1;
+        assert!(!maze.has_valid_shape(), "Too few columns");
+        maze.cols -= 1;
+        maze.grid = Grid::new(8, 15, Cell::Wall);
+        assert!(!maze.has_valid_shape(), "Missing row");
+    }
+
+    #[test]
+    fn test_open_fraction() {
+        let mut maze = CylinderMaze::new(6, 7);
+        let (start, end) = maze.generate_wilson();
+        let original = maze.grid().clone();
+        maze.open_fraction(0.0);
+        assert_eq!(*maze.grid(), original);
+
+        // A tree has 6 * 7 - 1 passages, out of 6 * 7 + 5 * 7 walls
+        let closed = 6 * 7 + 5 * 7 - (6 * 7 - 1);
+        maze.open_fraction(0.5);
+        assert_eq!(maze.passage_count(), 6 * 7 - 1 + closed / 2);
+        assert!(maze.can_solve(start, end));
+
+        maze.open_fraction(1.0);
+        assert_eq!(maze.passage_count(), 6 * 7 + 5 * 7);
+        assert!(maze.dead_ends().is_empty());
+
+        // With two columns, the seam and the middle wall are opened separately
+        let mut maze = CylinderMaze::new(3, 2);
+        maze.generate_wilson();
+        maze.open_fraction(1.0);
+        assert_eq!(maze.passage_count(), 3 * 2 + 2 * 2);
+        for gr in [1, 3, 5] {
+            assert!(maze.grid()[gr].iter().all(|cell| cell.is_open()));
+        }
+    }
+
+    #[test]
+    fn test_dead_ends() {
+        // A T shape: (0, 1) joins (0, 0), (0, 2) and (1, 1), which are all dead ends
+        let mut maze = CylinderMaze::new(3, 4);
+        maze.carve_passage((0, 1), (0, 0));
+        maze.carve_passage((0, 1), (0, 2));
+        maze.carve_passage((0, 1), (1, 1));
+        assert_eq!(maze.dead_ends(), vec![(0, 0), (0, 2), (1, 1)]);
+
+        // Joining the ends across the seam turns (0, 0) into a corridor
+        maze.carve_passage((0, 0), (0, 3));
+        assert_eq!(maze.dead_ends(), vec![(0, 2), (0, 3), (1, 1)]);
+    }
+
+    #[test]
+    fn test_branch_points() {
+        // A T shape branches at (0, 1)
+        let mut maze = CylinderMaze::new(3, 4);
+        maze.carve_passage((0, 1), (0, 0));
+        maze.carve_passage((0, 1), (0, 2));
+        assert!(maze.branch_points().is_empty());
+        maze.carve_passage((0, 1), (1, 1));
+        assert_eq!(maze.branch_points(), vec![(0, 1)]);
+
+        // Going around the seam from (0, 0) makes it a branch too
+        maze.carve_passage((0, 0), (0, 3));
+        maze.carve_passage((0, 0), (1, 0));
+        assert_eq!(maze.branch_points(), vec![(0, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn test_to_graph() {
+        let mut maze = CylinderMaze::new(2, 4);
+        maze.carve_passage((0, 0), (0, 3));
+        maze.carve_passage((0, 0), (1, 0));
+        let graph = maze.to_graph();
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph[&(1, 0)], vec![(0, 0)]);
+        assert_eq!(graph[&(0, 3)], vec![(0, 0)]);
+
+        // A perfect maze is a tree, with one fewer edge than cells
+        let mut maze = CylinderMaze::new(6, 7);
+        maze.generate_wilson();
+        let graph = maze.to_graph();
+        assert_eq!(graph.len(), 6 * 7);
+        let edges: usize = graph.values().map(Vec::len).sum();
+        assert_eq!(edges, 2 * (6 * 7 - 1));
+    }
+
+    #[test]
+    fn test_scaled_grid() {
+        let mut maze = CylinderMaze::new(2, 3);
+        maze.generate_wilson();
+        assert_eq!(*maze.grid(), maze.scaled_grid(1));
+        for corridor_cells in [1, 3] {
+            let scaled = maze.scaled_grid(corridor_cells);
+            let (rows, cols) = maze.scaled_size(corridor_cells);
+            assert_eq!((scaled.len(), scaled[0].len()), (rows, cols));
+            for (r, row) in scaled.iter().enumerate() {
+                for (c, &cell) in row.iter().enumerate() {
+                    assert_eq!(maze.scaled_cell(corridor_cells, r, c), cell);
+                }
+            }
+        }
+
+        let scaled = maze.scaled_grid(3);
+        assert_eq!(scaled.len(), 3 * 2 + 3);
+        assert!(scaled.iter().all(|row| row.len() == 3 * 3 + 4));
+
+        // Each square of the original grid becomes a block of the scaled grid
+        let scaled_index = |i: usize| i / 2 * 4 + i % 2;
+        for (r, row) in maze.grid().iter().enumerate() {
+            for (c, &cell) in row.iter().enumerate() {
+                assert_eq!(scaled[scaled_index(r)][scaled_index(c)], cell);
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_seam_column() {
+        let maze = CylinderMaze::new(3, 4);
+        let seam: Vec<usize> = (0..9).filter(|&c| maze.is_seam_column(c)).collect();
+        assert_eq!(seam, vec![0, 8]);
+    }
+
+    #[test]
+    fn test_grid_to_cell() {
+        let maze = CylinderMaze::new(3, 4);
+        for row in 0..3 {
+            for col in 0..4 {
+                let (gr, gc) = maze.cell_to_grid(row, col);
+                assert_eq!(maze.grid_to_cell(gr, gc), Some((row, col)));
+            }
+        }
+        assert_eq!(maze.grid_to_cell(0, 1), None, "Top edge");
+        assert_eq!(maze.grid_to_cell(1, 2), None, "Wall between cells");
+        assert_eq!(maze.grid_to_cell(2, 2), None, "Post");
+        assert_eq!(maze.grid_to_cell(7, 1), None, "Outside the grid");
+    }
+
+    #[test]
+    fn test_column_path_density() {
+        let mut maze = CylinderMaze::new(3, 4);
+        assert_eq!(maze.column_path_density(0), 0.0);
+
+        // A corridor straight down column 1, and one passage right from its middle
+        maze.carve_passage((0, 1), (1, 1));
+        maze.carve_passage((1, 1), (2, 1));
+        maze.carve_passage((1, 1), (1, 2));
+        assert_eq!(maze.column_path_density(1), 6.0 / 10.0);
+        assert_eq!(maze.column_path_density(2), 1.0 / 10.0);
+        assert_eq!(maze.column_path_density(3), 0.0);
+    }
+
+    #[test]
+    fn test_distances_from() {
+        // A corridor from (0, 0) around to (0, 3) across the seam, then down
+        let mut maze = CylinderMaze::new(2, 4);
+        maze.carve_passage((0, 0), (0, 3));
+        maze.carve_passage((0, 3), (1, 3));
+
+        let distances = maze.distances_from((0, 0));
+        assert_eq!(distances[0], vec![Some(0), None, None, Some(1)]);
+        assert_eq!(distances[1], vec![None, None, None, Some(2)]);
+    }
+
+    #[test]
+    fn test_reachable_within() {
+        let mut maze = CylinderMaze::new(6, 7);
+        let (start, _) = maze.generate_wilson();
+        let distances = maze.distances_from(start);
+
+        for max_steps in [0, 1, 5, 100] {
+            let reached = maze.reachable_within(start, max_steps);
+            for (row, row_distances) in distances.iter().enumerate() {
+                for (col, distance) in row_distances.iter().enumerate() {
+                    let within = distance.is_some_and(|d| d <= max_steps);
+                    assert_eq!(reached.contains(&(row, col)), within);
+                }
+            }
+        }
+        assert_eq!(maze.reachable_within(start, 0), HashSet::from([start]));
+    }
+
+    #[test]
+    fn test_move_end_farthest() {
+        for _ in 0..10 {
+            let mut maze = CylinderMaze::new(8, 8);
+            let (start, end) = maze.generate_wilson();
+            let farthest = maze.move_end_farthest(start, end);
+            assert!(maze.can_solve(start, farthest));
+
+            let distances = maze.distances_from(start);
+            for col in 0..8 {
+                assert!(distances[7][col] <= distances[farthest.0][farthest.1]);
+            }
+            let bottom = &maze.grid()[16];
+            assert_eq!(bottom.iter().filter(|&&c| c == Cell::Path).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_rotate_columns() {
+        let mut maze = CylinderMaze::new(6, 7);
+        let (start, end) = maze.generate_wilson();
+        let original = maze.grid().clone();
+
+        maze.rotate_columns(3);
+        let rotated = |(row, col): (usize, usize)| (row, (col + 3) % 7);
+        assert!(maze.can_solve(rotated(start), rotated(end)));
+        assert_eq!(maze.passage_count(), 6 * 7 - 1);
+        for row in maze.grid() {
+            assert_eq!(row[0], row[14], "Seam should match on both sides");
+        }
+
+        maze.rotate_columns(4);
+        assert_eq!(*maze.grid(), original, "Rotating all the way round");
+
+        // A huge rotation is reduced before it can overflow
+        maze.rotate_columns(usize::MAX);
+        let by = usize::MAX % 7;
+        assert_eq!(maze.start(), Some((start.0, (start.1 + by) % 7)));
+        maze.rotate_columns(7 - by);
+        assert_eq!(*maze.grid(), original);
+
+        let mut empty = CylinderMaze::new(3, 0);
+        empty.rotate_columns(2);
+    }
+
+    #[test]
+    fn test_subgrid() {
+        let mut maze = CylinderMaze::new(6, 8);
+        let (start, end) = maze.generate_wilson();
+
+        // Every column, every row is the whole maze
+        let whole = maze.subgrid(0..6, 0..8);
+        assert_eq!(whole.grid(), maze.grid());
+        assert!(whole.can_solve(start, end));
+
+        let crop = maze.subgrid(2..5, 3..7);
+        assert_eq!((crop.rows(), crop.cols()), (3, 4));
+        assert!(crop.has_valid_shape());
+        for row in 0..3 {
+            for col in 0..4 {
+                assert_eq!(crop.cell(row, col), maze.cell(row + 2, col + 3));
+                if col < 3 {
+                    assert_eq!(
+                        crop.is_open((row, col), (row, col + 1)),
+                        maze.is_open((row + 2, col + 3), (row + 2, col + 4))
+                    );
+                }
+            }
+            assert!(!crop.is_open((row, 3), (row, 0)), "Crop shouldn't wrap");
+        }
+        let grid = crop.grid();
+        assert!(grid[0].iter().all(|&cell| cell == Cell::Wall));
+        assert!(grid[grid.len() - 1].iter().all(|&cell| cell == Cell::Wall));
+    }
+
+    #[test]
+    fn test_unreachable_cell_count() {
+        let mut maze = CylinderMaze::new(6, 7);
+        assert_eq!(maze.unreachable_cell_count(), 0);
+        maze.generate_wilson();
+        assert_eq!(maze.unreachable_cell_count(), 0);
+
+        // Two separate pockets
+        let mut maze = CylinderMaze::new(3, 4);
+        maze.carve_passage((0, 0), (0, 1));
+        maze.carve_passage((2, 2), (2, 3));
+        maze.carve_passage((2, 3), (1, 3));
+        assert_eq!(maze.unreachable_cell_count(), 3);
+    }
+
+    #[test]
+    fn test_longest_path() {
+        for _ in 0..10 {
+            let mut maze = CylinderMaze::new(7, 8);
+            maze.generate_wilson();
+            let (length, first, second) = maze.longest_path();
+
+            let path = maze.solve_path(first, second).unwrap();
+            assert_eq!(path.len(), length + 1);
+
+            // Nothing is farther from either end
+            for distance in maze.distances_from(first).iter().flatten() {
+                assert!(distance.unwrap() <= length);
+            }
+        }
+
+        // A single corridor around the ring is as long as it can be
+        let mut maze = CylinderMaze::new(1, 5);
+        for col in 0..4 {
+            maze.carve_passage((0, col), (0, col + 1));
+        }
+        assert_eq!(maze.longest_path().0, 4);
+
+        // The first cell isn't always part of the maze
+        let mut maze = CylinderMaze::new(6, 5);
+        maze.generate_region(3..6);
+        let (length, first, second) = maze.longest_path();
+        assert!(length > 0);
+        assert!(first.0 >= 3 && second.0 >= 3);
+
+        let maze = CylinderMaze::new(3, 3);
+        assert_eq!(maze.longest_path(), (0, (0, 0), (0, 0)));
+    }
+
+    #[test]
+    fn test_direction() {
+        let maze = CylinderMaze::new(3, 4);
+        for dir in Direction::ALL {
+            assert_eq!(dir.opposite().opposite(), dir);
+            let (dr, dc) = dir.delta();
+            assert_eq!(dir.opposite().delta(), (-dr, -dc));
+            let next = dir.apply(&maze, (1, 1)).unwrap();
+            assert_eq!(dir.opposite().apply(&maze, next), Some((1, 1)));
+        }
+        assert_eq!(Direction::Up.apply(&maze, (0, 2)), None);
+        assert_eq!(Direction::Down.apply(&maze, (2, 2)), None);
+        assert_eq!(Direction::Left.apply(&maze, (1, 0)), Some((1, 3)));
+        assert_eq!(Direction::Right.apply(&maze, (1, 3)), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_get_neighbors_with_dir() {
+        let maze = CylinderMaze::new(3, 4);
+        assert_eq!(
+            maze.get_neighbors_with_dir(0, 0),
+            vec![
+                (Direction::Down, (1, 0)),
+                (Direction::Left, (0, 3)),
+                (Direction::Right, (0, 1)),
+            ]
+        );
+        assert_eq!(
+            maze.get_neighbors_with_dir(2, 3),
+            vec![
+                (Direction::Up, (1, 3)),
+                (Direction::Left, (2, 2)),
+                (Direction::Right, (2, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solve_path() {
+        let mut maze = CylinderMaze::new(3, 4);
+        maze.carve_passage((0, 0), (0, 3));
+        maze.carve_passage((0, 3), (1, 3));
+        maze.carve_passage((1, 3), (2, 3));
+        maze.carve_passage((0, 0), (0, 1));
+
+        assert_eq!(
+            maze.solve_path((0, 1), (2, 3)),
+            Some(vec![(0, 1), (0, 0), (0, 3), (1, 3), (2, 3)])
+        );
+        assert_eq!(maze.solve_path((0, 1), (0, 1)), Some(vec![(0, 1)]));
+        assert_eq!(maze.solve_path((0, 1), (2, 0)), None);
+    }
+
+    #[test]
+    fn test_mark_solution() {
+        let mut maze = CylinderMaze::new(6, 7);
+        let (start, end) = maze.generate_wilson();
+        let original = maze.grid().clone();
+        assert!(maze.mark_solution(start, end));
+
+        // One square per cell on the route, and one per wall between them, plus the seam
+        let solution = maze.solve_path(start, end).unwrap();
+        let marked = maze.grid().iter().flatten();
+        let marked = marked.filter(|&&cell| cell == Cell::Solution).count();
+        let seam = maze
+            .grid()
+            .iter()
+            .filter(|row| row[0] == Cell::Solution)
+            .count();
+        assert_eq!(marked, 2 * solution.len() - 1 + seam);
+
+        // Solution squares are still paths for everything else
+        assert_eq!(maze.solve_path(start, end), Some(solution));
+        assert_eq!(maze.passage_count(), 6 * 7 - 1);
+        let rows = maze.render_rows(start, end);
+        assert_eq!(rows.concat().matches('.').count(), marked - 2);
+
+        maze.clear_solution();
+        assert_eq!(*maze.grid(), original);
+
+        let mut maze = CylinderMaze::new(3, 3);
+        assert!(!maze.mark_solution((0, 0), (2, 2)));
+    }
+
+    #[test]
+    fn test_solution() {
+        let mut maze = CylinderMaze::new(6, 7);
+        assert_eq!(maze.solution(), None);
+        let (start, end) = maze.generate_wilson();
+        let solution = maze.solve_path(start, end).unwrap();
+        assert_eq!(maze.solution(), Some(&solution[..]));
+
+        // Moving the end or the seam finds the route again
+        let end = maze.move_end_farthest(start, end);
+        assert_eq!(maze.solution(), maze.solve_path(start, end).as_deref());
+        maze.rotate_columns(3);
+        let rotate = |(row, col): (usize, usize)| (row, (col + 3) % 7);
+        let rotated = maze.solve_path(rotate(start), rotate(end));
+        assert_eq!(maze.solution(), rotated.as_deref());
+
+        maze.set_endpoints((0, 0), (0, 0));
+        assert_eq!(maze.solution(), Some(&[(0, 0)][..]));
+        maze.reset();
+        maze.set_endpoints((0, 0), (5, 6));
+        assert_eq!(maze.solution(), None);
+    }
+
+    #[test]
+    fn test_start_and_end() {
+        let mut maze = CylinderMaze::new(5, 6);
+        assert_eq!((maze.start(), maze.end()), (None, None));
+        assert!(!maze.is_solvable());
+
+        let (start, end) = maze.generate_recursive_division();
+        assert_eq!((maze.start(), maze.end()), (Some(start), Some(end)));
+        assert!(maze.is_solvable());
+
+        maze.reset();
+        let (start, end) = maze.generate_spiral(1.0);
+        assert_eq!((maze.start(), maze.end()), (Some(start), Some(end)));
+        maze.set_endpoints(end, start);
+        assert_eq!((maze.start(), maze.end()), (Some(end), Some(start)));
+    }
+
+    #[test]
+    fn test_solve_weighted() {
+        // Two ways round the ring: two steps right, or three left across the seam
+        let mut maze = CylinderMaze::new(1, 5);
+        for col in 0..5 {
+            maze.carve_passage((0, col), (0, (col + 1) % 5));
+        }
+        let cost = |dir| if dir == Direction::Right { 10 } else { 1 };
+        assert_eq!(
+            maze.solve_weighted((0, 0), (0, 2), cost),
+            Some((vec![(0, 0), (0, 4), (0, 3), (0, 2)], 3))
+        );
+        assert_eq!(
+            maze.solve_weighted((0, 0), (0, 2), |_| 1),
+            Some((vec![(0, 0), (0, 1), (0, 2)], 2))
+        );
+
+        // In a perfect maze the only route is the shortest one
+        let mut maze = CylinderMaze::new(6, 7);
+        let (start, end) = maze.generate_wilson();
+        let solution = maze.solve_path(start, end).unwrap();
+        let (path, total) = maze.solve_weighted(start, end, |_| 2).unwrap();
+        assert_eq!(total as usize, 2 * (solution.len() - 1));
+        assert_eq!(path, solution);
+
+        let maze = CylinderMaze::new(2, 2);
+        assert_eq!(maze.solve_weighted((0, 0), (1, 1), |_| 1), None);
+
+        // A huge cost doesn't overflow the total
+        let mut maze = CylinderMaze::new(3, 1);
+        maze.carve_passage((0, 0), (1, 0));
+        maze.carve_passage((1, 0), (2, 0));
+        let cost = |dir| if dir == Direction::Up { u32::MAX } else { 1 };
+        assert_eq!(maze.solve_weighted((2, 0), (0, 0), cost), None);
+        assert_eq!(
+            maze.solve_weighted((1, 0), (0, 0), cost),
+            Some((vec![(1, 0), (0, 0)], u32::MAX))
+        );
+        assert_eq!(maze.solve_weighted((0, 0), (2, 0), cost).unwrap().1, 2);
+    }
+
+    #[test]
+    fn test_solution_squares() {
+        // Across the seam from (0, 0) to (0, 2), then down
+        let mut maze = CylinderMaze::new(2, 3);
+        maze.carve_passage((0, 0), (0, 2));
+        maze.carve_passage((0, 2), (1, 2));
+        assert_eq!(
+            maze.solution_squares((0, 0), (1, 2)),
+            Some(vec![(1, 1), (1, 0), (1, 6), (1, 5), (2, 5), (3, 5)])
+        );
+        assert_eq!(maze.solution_squares((0, 0), (1, 0)), None);
+    }
+
+    #[test]
+    fn test_solve_wall_follower() {
+        for _ in 0..10 {
+            let mut maze = CylinderMaze::new(8, 9);
+            let (start, end) = maze.generate_wilson();
+            let path = maze
+                .solve_wall_follower(start, end)
+                .expect("Wall follower should solve a perfect maze");
+
+            assert_eq!(path.first(), Some(&start));
+            assert_eq!(path.last(), Some(&end));
+            for step in path.windows(2) {
+                assert!(maze.is_open(step[0], step[1]), "Walked through a wall");
+            }
+        }
+
+        // A closed room with no way to the end
+        let mut maze = CylinderMaze::new(3, 3);
+        maze.carve_passage((0, 0), (0, 1));
+        assert_eq!(maze.solve_wall_follower((0, 0), (2, 2)), None);
+    }
+
+    #[test]
+    fn test_total_turns() {
+        // Straight down
+        let mut maze = CylinderMaze::new(3, 4);
+        maze.carve_passage((0, 1), (1, 1));
+        maze.carve_passage((1, 1), (2, 1));
+        assert_eq!(maze.total_turns((0, 1), (2, 1)), Some(0));
+
+        // Left across the seam, down, left and down again
+        maze.carve_passage((0, 1), (0, 0));
+        maze.carve_passage((0, 0), (0, 3));
+        maze.carve_passage((0, 3), (1, 3));
+        maze.carve_passage((1, 3), (1, 2));
+        maze.carve_passage((1, 2), (2, 2));
+        assert_eq!(maze.total_turns((0, 1), (2, 2)), Some(3));
+
+        assert_eq!(maze.total_turns((0, 1), (2, 0)), None);
+    }
+
+    #[test]
+    fn test_complexity_score() {
+        // A straight corridor from top to bottom, with nothing else open
+        let mut corridor = CylinderMaze::new(4, 4);
+        for row in 0..3 {
+            corridor.carve_passage((row, 0), (row + 1, 0));
+        }
+        let only_solution = ComplexityWeights {
+            solution_length: 1.0,
+            dead_ends: 0.0,
+            branch_points: 0.0,
+            branch_depth: 0.0,
+        };
+        let score = corridor.complexity_score_weighted((0, 0), (3, 0), &only_solution);
+        assert_eq!(score, 4.0 / 16.0);
+        assert_eq!(corridor.complexity_score((0, 0), (3, 1)), 0.0, "Unsolvable");
+
+        let mut maze = CylinderMaze::new(10, 10);
         let (start, end) = maze.generate_wilson();
+        assert!(maze.complexity_score(start, end) > 0.0);
+    }
+
+    #[test]
+    fn test_validate_endpoints() {
+        let maze = CylinderMaze::new(4, 5);
+        assert!(maze.validate_endpoints((0, 2), (3, 4), true).is_ok());
+        assert!(maze.validate_endpoints((1, 2), (2, 4), false).is_ok());
+        assert!(maze.validate_endpoints((1, 2), (3, 4), true).is_err());
+        assert!(maze.validate_endpoints((0, 2), (2, 4), true).is_err());
+        assert!(maze.validate_endpoints((0, 5), (3, 4), false).is_err());
+        assert!(maze.validate_endpoints((0, 2), (4, 4), false).is_err());
+    }
+
+    #[test]
+    fn test_render_compact() {
+        // A passage across the seam in the top row, down column 2, and out of the top and
+        // bottom edges
+        let mut maze = CylinderMaze::new(2, 3);
+        maze.carve_passage((0, 0), (0, 2));
+        maze.carve_passage((0, 2), (1, 2));
+        maze.open_endpoint((0, 0), false);
+        maze.open_endpoint((1, 2), true);
+        maze.mark_solution((0, 0), (1, 2));
+        assert_eq!(
+            maze.render_compact((0, 0), (1, 2)),
+            vec!["+ +-+-+", " S| |. ", "+-+-+ +", "| | |E|", "+-+-+ +"]
+        );
+    }
+
+    #[test]
+    fn test_render_rows() {
+        let mut maze = CylinderMaze::new(1, 3);
+        maze.carve_passage((0, 0), (0, 1));
+        maze.open_endpoint((0, 0), false);
+        maze.open_endpoint((0, 1), true);
+        assert_eq!(
+            maze.render_rows((0, 0), (0, 1)),
+            vec!["█ █████", "█S E███", "███ ███"]
+        );
+        assert_eq!(
+            maze.render_grid(None, &DisplayStyle::default()),
+            vec!["█ █████", "█   ███", "███ ███"]
+        );
+
+        let style = DisplayStyle {
+            wall: '#',
+            path: '.',
+            ..DisplayStyle::default()
+        };
+        assert_eq!(
+            maze.render_rows_styled((0, 0), (0, 1), &style),
+            vec!["#.#####", "#S.E###", "###.###"]
+        );
+
+        let style = DisplayStyle {
+            repeat_seam: true,
+            ..style
+        };
+        assert_eq!(
+            maze.render_rows_styled((0, 0), (0, 1), &style),
+            vec!["#.#####.", "#S.E###S", "###.####"]
+        );
+    }
+
+    #[test]
+    fn test_cell() {
+        let mut maze = CylinderMaze::new(2, 3);
+        maze.carve_passage((1, 1), (1, 2));
+        assert_eq!(maze.cell(0, 0), Some(Cell::Wall));
+        assert_eq!(maze.cell(1, 2), Some(Cell::Path));
+        assert_eq!(maze.cell(2, 0), None);
+        assert_eq!(maze.cell(0, 3), None);
+    }
+
+    #[test]
+    fn test_grid_hash() {
+        let mut maze = CylinderMaze::new(6, 7);
+        maze.generate_wilson();
+        let hash = maze.grid_hash();
+        assert_eq!(maze.grid_hash(), hash);
+
+        maze.rotate_columns(1);
+        assert_ne!(maze.grid_hash(), hash);
+        maze.rotate_columns(6);
+        assert_eq!(maze.grid_hash(), hash);
+
+        // Same walls, different shape
+        assert_ne!(
+            CylinderMaze::new(2, 3).grid_hash(),
+            CylinderMaze::new(3, 2).grid_hash()
+        );
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let mut first = CylinderMaze::with_seed(8, 10, 42);
+        let mut second = CylinderMaze::with_seed(8, 10, 42);
+        assert_eq!(first.generate_wilson(), second.generate_wilson());
+        assert_eq!(first.grid_hash(), second.grid_hash());
+
+        // The maze for a seed must never change, or published seeds stop working
+        assert_eq!(first.grid_hash(), 17392638649594394264);
+
+        first.reseed(43);
+        first.reset();
+        first.generate_wilson();
+        assert_ne!(first.grid_hash(), second.grid_hash());
+
+        for seed in 0..5 {
+            let mut first = CylinderMaze::with_seed(6, 7, seed);
+            let mut second = CylinderMaze::with_seed(6, 7, seed);
+            first.generate_recursive_division();
+            second.generate_recursive_division();
+            assert_eq!(first.grid(), second.grid());
+            first.reset();
+            second.reset();
+            first.generate_symmetric();
+            second.generate_symmetric();
+            assert_eq!(first.grid(), second.grid());
+        }
+    }
+
+    #[test]
+    fn test_generate_batch() {
+        let seeds: Vec<u64> = (100..120).collect();
+        let batch = CylinderMaze::generate_batch(6, 7, &seeds, CylinderMaze::generate_wilson);
+        assert_eq!(batch.len(), seeds.len());
+
+        for (&seed, (maze, endpoints)) in seeds.iter().zip(&batch) {
+            let mut single = CylinderMaze::with_seed(6, 7, seed);
+            assert_eq!(single.generate_wilson(), *endpoints);
+            assert_eq!(single.grid_hash(), maze.grid_hash());
+        }
+
+        let batch = CylinderMaze::generate_batch(6, 7, &[], CylinderMaze::generate_wilson);
+        assert!(batch.is_empty());
+    }
+
+    #[test]
+    fn test_find_seed() {
+        let mut maze = CylinderMaze::with_seed(6, 7, 37);
+        maze.generate_wilson();
+        let hash = maze.grid_hash();
+
+        let seed = CylinderMaze::find_seed(
+            6,
+            7,
+            CylinderMaze::generate_wilson,
+            |maze| maze.grid_hash() == hash,
+            100,
+        );
+        assert_eq!(seed, Some(37));
 
+        // The first seed to match, however the threads share them out
+        let seed = CylinderMaze::find_seed(6, 7, CylinderMaze::generate_wilson, |_| true, 100);
+        assert_eq!(seed, Some(0));
+        let seed = CylinderMaze::find_seed(6, 7, CylinderMaze::generate_wilson, |_| false, 100);
+        assert_eq!(seed, None);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut maze = CylinderMaze::new(8, 8);
+        maze.generate_wilson();
+        maze.reset();
+        assert!(maze.grid().iter().flatten().all(|&cell| cell == Cell::Wall));
+        assert_eq!((maze.start(), maze.end()), (None, None));
+        assert_eq!(maze.solution(), None);
+
+        let (start, end) = maze.generate_wilson();
         assert!(
             maze.can_solve(start, end),
-            "Large maze should be solvable from S to E"
+            "Regenerated maze should be solvable"
+        );
+    }
+
+    #[test]
+    fn test_is_open() {
+        let mut maze = CylinderMaze::new(3, 4);
+        maze.carve_passage((0, 0), (1, 0));
+        maze.carve_passage((1, 0), (1, 1));
+        maze.carve_passage((2, 0), (2, 3));
+
+        assert!(maze.is_open((0, 0), (1, 0)));
+        assert!(maze.is_open((1, 1), (1, 0)));
+        assert!(maze.is_open((2, 0), (2, 3)), "Passage across the seam");
+        assert!(maze.is_open((2, 3), (2, 0)), "Passage across the seam");
+        assert!(!maze.is_open((0, 0), (0, 1)), "Wall between adjacent cells");
+        assert!(
+            !maze.is_open((0, 0), (1, 1)),
+            "Diagonal cells are not adjacent"
         );
+        assert!(
+            !maze.is_open((0, 0), (2, 0)),
+            "Cells two rows apart are not adjacent"
+        );
+        assert!(!maze.is_open((0, 0), (3, 0)), "Out of bounds");
+
+        // With two columns, carving from column 1 to 0 goes through the seam
+        let mut maze = CylinderMaze::new(2, 2);
+        maze.carve_passage((0, 1), (0, 0));
+        assert_eq!(maze.grid()[1][2], Cell::Wall, "Only the seam is carved");
+        maze.carve_passage((1, 0), (1, 1));
+        assert!(maze.is_open((0, 0), (0, 1)));
+        assert!(maze.is_open((1, 1), (1, 0)));
+        assert!(!maze.is_open((0, 0), (1, 0)));
+
+        let mut maze = CylinderMaze::new(2, 1);
+        maze.carve_passage((0, 0), (1, 0));
+        assert!(!maze.is_open((0, 0), (0, 0)), "A cell isn't next to itself");
     }
 
     #[test]
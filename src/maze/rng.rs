@@ -0,0 +1,87 @@
+use std::cell::Cell;
+
+/// The random number generator behind every maze generator. It's SplitMix64, written out
+/// here rather than taken from `rand`, so that a seed makes the same maze forever: the
+/// sequence and the way it's turned into ranges and probabilities are fixed by this file,
+/// not by whichever version of `rand` is in use. Changing anything here changes every
+/// seeded maze.
+///
+/// The state is in a `Cell`, so the maze can draw numbers while it's borrowed.
+#[derive(Debug, Clone)]
+pub(super) struct MazeRng {
+    state: Cell<u64>,
+}
+
+impl MazeRng {
+    pub(super) fn from_seed(seed: u64) -> Self {
+        MazeRng {
+            state: Cell::new(seed),
+        }
+    }
+
+    /// The next 64 random bits
+    pub(super) fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, from the high bits of a 64 by 64 bit multiply. The bias is at
+    /// most `n / 2^64`, which is nothing for a maze.
+    pub(super) fn below(&self, n: usize) -> usize {
+        assert!(n > 0, "Can't pick a number below zero");
+        ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+    }
+
+    /// A number in `range`
+    pub(super) fn in_range(&self, range: std::ops::Range<usize>) -> usize {
+        range.start + self.below(range.len())
+    }
+
+    /// A number in `[0, 1)`, from the top 53 bits
+    pub(super) fn unit(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// `true` with probability `p`
+    pub(super) fn chance(&self, p: f64) -> bool {
+        self.unit() < p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splitmix64_reference() {
+        // The first outputs of the reference SplitMix64 for seed 1234567
+        let rng = MazeRng::from_seed(1234567);
+        let outputs: Vec<u64> = (0..5).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            outputs,
+            vec![
+                6457827717110365317,
+                3203168211198807973,
+                9817491932198370423,
+                4593380528125082431,
+                16408922859458223821,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ranges() {
+        let rng = MazeRng::from_seed(7);
+        for _ in 0..1000 {
+            assert!(rng.below(3) < 3);
+            assert!((5..9).contains(&rng.in_range(5..9)));
+            assert!((0.0..1.0).contains(&rng.unit()));
+        }
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+    }
+}
@@ -0,0 +1,87 @@
+use super::{CylinderConfig, MazeSurface};
+use crate::maze::CylinderMaze;
+use anyhow::{Result, bail};
+use std::f64::consts::TAU;
+
+/// Check that the maze cylinder and the outer shell made from `config` fit together with
+/// at least `min_clearance` mm between them: around the outside of the maze, and around
+/// the tooth on the shell as it follows a corridor. The sizes match the OpenSCAD export.
+/// A maze carved into the inside of a tube is as wide as the tube, so it never fits in
+/// the shell.
+pub fn check_fit(maze: &CylinderMaze, config: &CylinderConfig, min_clearance: f64) -> Result<()> {
+    let (rows, cols) = maze.scaled_size(config.corridor_cells);
+    let square_width = config.circumference / cols as f64;
+    let square_height = config.height / rows as f64;
+    let radius = config.circumference / TAU;
+    let (shell_radius, _) = config.shell_radii();
+
+    let maze_radius = match config.surface {
+        MazeSurface::Outer => radius,
+        MazeSurface::Inner => radius + square_width,
+    };
+    // Gaps are compared as they're reported, to the nearest micron, so an error never says
+    // a gap is less than the same number
+    let gap = round_to_micron(shell_radius - maze_radius);
+    if gap < min_clearance {
+        bail!(
+            "The maze is {maze_radius:.3}mm in radius and the inside of the shell is \
+             {shell_radius:.3}mm, leaving {gap:.3}mm between them, less than {min_clearance}mm"
+        );
+    }
+
+    // The tooth is 90% of the corridor's width and height, so the gap is 5% on each side
+    let corridor_cells = config.corridor_cells as f64;
+    let tooth_gap = round_to_micron(0.05 * corridor_cells * square_width.min(square_height));
+    if tooth_gap < min_clearance {
+        bail!(
+            "The tooth on the shell leaves {tooth_gap:.3}mm on each side in the corridors, \
+             less than {min_clearance}mm"
+        );
+    }
+
+    Ok(())
+}
+
+fn round_to_micron(mm: f64) -> f64 {
+    (mm * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_fit() {
+        let maze = CylinderMaze::new(10, 20);
+        let config = CylinderConfig::default();
+        assert!(check_fit(&maze, &config, 0.0).is_ok());
+        assert!(check_fit(&maze, &config, 0.19).is_err());
+
+        // Wider corridors leave more room around the tooth
+        let wide = CylinderConfig {
+            corridor_cells: 4,
+            ..config
+        };
+        assert!(check_fit(&maze, &wide, 0.19).is_ok());
+        let tight = CylinderConfig {
+            clearance: 0.1,
+            ..wide
+        };
+        assert!(check_fit(&maze, &tight, 0.19).is_err());
+
+        let inner = CylinderConfig {
+            surface: MazeSurface::Inner,
+            ..config
+        };
+        assert!(check_fit(&maze, &inner, 0.0).is_err());
+
+        // A big maze on the default cylinder has a tight tooth, but nothing overlaps
+        let big = CylinderMaze::new(30, 50);
+        assert!(check_fit(&big, &config, 0.0).is_ok());
+
+        // Gaps are reported and compared to the micron, so 0.0495mm is enough for 0.05mm
+        let error = check_fit(&big, &config, 0.1).unwrap_err().to_string();
+        assert!(error.contains("leaves 0.049mm"), "{error}");
+        assert!(check_fit(&CylinderMaze::new(10, 50), &config, 0.05).is_ok());
+    }
+}
@@ -1,3 +1,13 @@
+mod fit;
 mod openscad;
+mod printability;
+mod solution;
 
-pub use openscad::{make_outer_openscad, maze_to_openscad};
+pub use fit::check_fit;
+pub(crate) use openscad::grid_square_to_cylindrical;
+pub use openscad::{
+    CylinderConfig, MazeSurface, cell_center, cell_to_cylindrical, export_all, make_outer_openscad,
+    maze_to_openscad, write_maze_openscad, write_outer_openscad,
+};
+pub use printability::{PrintabilityReport, printability_report};
+pub use solution::{solution_to_openscad, write_solution_openscad};
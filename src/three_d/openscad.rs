@@ -1,91 +1,360 @@
-use crate::maze::{Cell, CylinderMaze};
+use crate::maze::CylinderMaze;
 use anyhow::Result;
 use std::f64::consts::TAU;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Which side of the cylinder wall the maze is carved into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MazeSurface {
+    /// The outside of a solid cylinder, for the outer shell to slide over
+    #[default]
+    Outer,
+    /// The inside of a tube, for a ball dropped in at the top
+    Inner,
+}
+
+/// Physical dimensions shared by the maze cylinder and the outer shell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CylinderConfig {
+    pub height: f64,
+    pub circumference: f64,
+    pub hollow: bool,
+    pub base_height_frac: f64,
+    pub base_radius_frac: f64,
+    /// Width of each corridor, in multiples of the wall thickness
+    pub corridor_cells: usize,
+    /// Gap between the maze cylinder and the inside of the outer shell, so the maze can
+    /// turn freely. Printers differ in how much they need.
+    pub clearance: f64,
+    /// Rows and columns of round holes through the outer shell, so the maze can be seen
+    /// and heard inside. No holes if either is zero.
+    pub vent_rows: usize,
+    pub vent_cols: usize,
+    pub vent_diameter: f64,
+    /// Which side of the cylinder the maze is on. On the inside, the cylinder is a tube one
+    /// wall thick, and `hollow` makes no difference.
+    pub surface: MazeSurface,
+    /// Number of thin ribs from the axis to the inside wall of a hollow maze, to hold up
+    /// a tall cylinder while it prints. They can be cut out afterwards. There are none
+    /// with `openwork`, since they'd block the paths cut through the wall.
+    pub support_ribs: usize,
+    /// Cut the paths right through the wall, so only the maze's walls are left standing,
+    /// on a hollow cylinder. The walls of a perfect maze all join up, but loops in the
+    /// paths leave pieces of wall loose.
+    pub openwork: bool,
+}
+
+impl Default for CylinderConfig {
+    fn default() -> Self {
+        CylinderConfig {
+            height: 60.0,
+            circumference: 100.0,
+            hollow: false,
+            base_height_frac: 0.05,
+            base_radius_frac: 1.1,
+            corridor_cells: 1,
+            clearance: 0.2,
+            vent_rows: 0,
+            vent_cols: 0,
+            vent_diameter: 3.0,
+            surface: MazeSurface::Outer,
+            support_ribs: 0,
+            openwork: false,
+        }
+    }
+}
+
+impl CylinderConfig {
+    /// Inner and outer radius of the outer shell. The inside clears the maze by
+    /// `clearance`, and the wall is at least 1.2mm thick.
+    pub fn shell_radii(&self) -> (f64, f64) {
+        let radius = self.circumference / TAU;
+        let inner_radius = radius + self.clearance;
+        let outer_radius = (radius * 1.1).max(inner_radius + 1.2);
+        (inner_radius, outer_radius)
+    }
+}
+
+/// Where the middle of logical cell `(row, col)` ends up in the OpenSCAD model made with
+/// `config`, as `[x, y, z]`, halfway down its groove. Row 0 is at the bottom of the model.
+pub fn cell_center(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    row: usize,
+    col: usize,
+) -> [f64; 3] {
+    let (grid_row, grid_col) = maze.cell_to_grid(row, col);
+    grid_square_center(maze, config, grid_row, grid_col)
+}
+
+/// Where the middle of logical cell `(row, col)` is around and up the cylinder made with
+/// `config`, as an angle in radians from the x axis and a height in mm from the bottom.
+/// The solution tube and the wrap SVG place every square of the grid with the same
+/// calculation, so they line up with the model and with each other at the seam.
+pub fn cell_to_cylindrical(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    row: usize,
+    col: usize,
+) -> (f64, f64) {
+    let (grid_row, grid_col) = maze.cell_to_grid(row, col);
+    grid_square_to_cylindrical(maze, config, grid_row, grid_col)
+}
+
+/// The angle in radians and height in mm of the middle of a square of the maze's grid.
+/// This is the one place they're worked out, for anything placed on or matched to the
+/// model.
+pub(crate) fn grid_square_to_cylindrical(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    grid_row: usize,
+    grid_col: usize,
+) -> (f64, f64) {
+    // The position of the square's middle in the scaled grid, where a cell is
+    // `corridor_cells` squares wide and a wall is one
+    let corridor_cells = config.corridor_cells;
+    let scaled = |i: usize| {
+        let first = i / 2 * (corridor_cells + 1);
+        if i % 2 == 1 {
+            (first + 1) as f64 + (corridor_cells as f64 - 1.0) / 2.0
+        } else {
+            first as f64
+        }
+    };
+    let (scaled_rows, scaled_cols) = maze.scaled_size(corridor_cells);
+
+    let angle = TAU * scaled(grid_col) / scaled_cols as f64;
+    let height = (scaled(grid_row) + 0.5) * config.height / scaled_rows as f64;
+    (angle, height)
+}
+
+/// Where the middle of a square of the maze's grid ends up in the OpenSCAD model, as
+/// `[x, y, z]`. For a path square this is halfway down the groove cut for it.
+pub(super) fn grid_square_center(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    grid_row: usize,
+    grid_col: usize,
+) -> [f64; 3] {
+    let radius = config.circumference / TAU;
+    let (_, scaled_cols) = maze.scaled_size(config.corridor_cells);
+    let seg_scale_x = config.circumference / scaled_cols as f64;
+    let groove_radius = match config.surface {
+        MazeSurface::Outer => radius - seg_scale_x * 0.225,
+        MazeSurface::Inner => radius + seg_scale_x * 0.225,
+    };
+    let (angle, height) = grid_square_to_cylindrical(maze, config, grid_row, grid_col);
+    [
+        groove_radius * angle.cos(),
+        groove_radius * angle.sin(),
+        height,
+    ]
+}
 
 /// Generate OpenSCAD code for the maze cylinder
 pub fn maze_to_openscad(
     maze: &CylinderMaze,
-    height: f64,
-    circumference: f64,
+    config: &CylinderConfig,
     filename: &str,
-    hollow: bool,
 ) -> Result<()> {
+    // Write the whole model, a piece at a time
+    let mut file = BufWriter::new(File::create(format!("{filename}_whole.scad"))?);
+    write_maze_openscad(maze, config, &mut file)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Write OpenSCAD code for the maze cylinder to `writer`, a line at a time. The paths are
+/// read straight from the maze's grid with `scaled_cell`, so even for a huge maze neither
+/// the text nor a scaled copy of the grid is held in memory.
+pub fn write_maze_openscad(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    writer: &mut impl Write,
+) -> Result<()> {
+    let CylinderConfig {
+        height,
+        circumference,
+        hollow,
+        base_height_frac,
+        base_radius_frac,
+        corridor_cells,
+        surface,
+        support_ribs,
+        openwork,
+        ..
+    } = *config;
+    let hollow = hollow || openwork;
     let radius = circumference / TAU;
-    let grid = maze.grid();
+    debug_assert!(
+        maze.has_valid_shape(),
+        "Grid doesn't match the {}x{} maze",
+        maze.rows(),
+        maze.cols()
+    );
+    let (rows, cols) = maze.scaled_size(corridor_cells);
+    let is_open = |row: usize, col: usize| maze.scaled_cell(corridor_cells, row, col).is_open();
 
-    let seg_scale_x = circumference / grid[0].len() as f64;
-    let seg_scale_z = height / grid.len() as f64;
-    let height = seg_scale_z * grid.len() as f64;
+    let seg_scale_x = circumference / cols as f64;
+    let seg_scale_z = height / rows as f64;
+    let height = seg_scale_z * rows as f64;
 
-    let mut scad = String::new();
+    // On the inside the paths are cut outward from the bore into a wall around it. Cut
+    // right through, they go a little past both sides of the wall.
+    let (solid_radius, carve_x, carve_depth) = match (surface, openwork) {
+        (MazeSurface::Outer, false) => ("radius", "radius - seg_scale_x * 0.45", 1.01),
+        (MazeSurface::Outer, true) => ("radius", "radius - seg_scale_x * 1.1", 1.2),
+        (MazeSurface::Inner, false) => (
+            "(radius + seg_scale_x)",
+            "radius - seg_scale_x * 0.56",
+            1.01,
+        ),
+        (MazeSurface::Inner, true) => ("(radius + seg_scale_x)", "radius - seg_scale_x * 0.1", 1.2),
+    };
 
     // Define parameters
-    scad.push_str(&format!("radius = {radius};\n"));
-    scad.push_str(&format!("seg_scale_x = {seg_scale_x};\n"));
-    scad.push_str(&format!("seg_scale_z = {seg_scale_z};\n"));
-    scad.push_str(&format!("height = {height};\n"));
-    scad.push_str(&format!("rows = {};\n", grid.len()));
-    scad.push_str(&format!("cols = {};\n", grid[0].len()));
-    scad.push('\n');
+    writeln!(writer, "radius = {radius};")?;
+    writeln!(writer, "seg_scale_x = {seg_scale_x};")?;
+    writeln!(writer, "seg_scale_z = {seg_scale_z};")?;
+    writeln!(writer, "height = {height};")?;
+    writeln!(writer, "rows = {rows};")?;
+    writeln!(writer, "cols = {cols};")?;
+    writeln!(writer, "base_height = height * {base_height_frac};")?;
+    writeln!(writer, "base_radius = {solid_radius} * {base_radius_frac};")?;
+    writeln!(writer)?;
 
-    // Build maze data array - collect path cells
-    scad.push_str("// Maze data: [row, col] pairs for path cells\n");
-    scad.push_str("maze_paths = [\n");
-    for (row, row_cells) in grid.iter().enumerate() {
-        for (col, cell) in row_cells.iter().enumerate() {
-            if *cell == Cell::Path {
-                scad.push_str(&format!("  [{row}, {col}],\n"));
+    // Build maze data array. Path squares running straight down a column are merged into
+    // one segment, so OpenSCAD has far fewer cubes to subtract. Squares side by side
+    // can't be merged, because the cubes are flat and the cylinder isn't.
+    writeln!(
+        writer,
+        "// Maze data: [row, col, length] for runs of path cells down each column"
+    )?;
+    writeln!(writer, "maze_paths = [")?;
+    for col in 0..cols {
+        let mut row = 0;
+        while row < rows {
+            if !is_open(row, col) {
+                row += 1;
+                continue;
             }
+            let length = (row..rows).take_while(|&row| is_open(row, col)).count();
+            writeln!(writer, "  [{row}, {col}, {length}],")?;
+            row += length;
         }
     }
-    scad.push_str("];\n\n");
+    writeln!(writer, "];")?;
+    writeln!(writer)?;
 
     // Generate the maze using OpenSCAD for loop
-    scad.push_str("union() {\n");
-    scad.push_str("  difference() {\n");
-    scad.push_str("    cylinder(r=radius, h=height, $fn=360);\n");
-    scad.push_str("    \n");
-    scad.push_str("    // Carve out path segments\n");
-    scad.push_str("    for (path = maze_paths) {\n");
-    scad.push_str("      row = path[0];\n");
-    scad.push_str("      col = path[1];\n");
-    scad.push_str("      angle = 360 * col / cols;\n");
-    scad.push_str("      z_pos = row * seg_scale_z;\n");
-    scad.push_str("      \n");
-    scad.push_str("      rotate([0, 0, angle])\n");
-    scad.push_str("        translate([radius - seg_scale_x * 0.45, -seg_scale_x / 2, z_pos])\n");
-    scad.push_str("          cube([seg_scale_x * 1.01, seg_scale_x, seg_scale_z * 1.01]);\n");
-    scad.push_str("    }\n");
-    if hollow {
-        scad.push_str("    cylinder(r=radius-seg_scale_x, h=height+0.1, $fn=360);\n");
+    writeln!(writer, "union() {{")?;
+    writeln!(writer, "  difference() {{")?;
+    writeln!(writer, "    cylinder(r={solid_radius}, h=height, $fn=360);")?;
+    writeln!(writer, "    ")?;
+    writeln!(writer, "    // Carve out path segments")?;
+    writeln!(writer, "    for (path = maze_paths) {{")?;
+    writeln!(writer, "      row = path[0];")?;
+    writeln!(writer, "      col = path[1];")?;
+    writeln!(writer, "      length = path[2];")?;
+    writeln!(writer, "      angle = 360 * col / cols;")?;
+    writeln!(writer, "      z_pos = row * seg_scale_z;")?;
+    writeln!(writer, "      ")?;
+    writeln!(writer, "      rotate([0, 0, angle])")?;
+    writeln!(
+        writer,
+        "        translate([{carve_x}, -seg_scale_x / 2, z_pos])"
+    )?;
+    writeln!(
+        writer,
+        "          cube([seg_scale_x * {carve_depth}, seg_scale_x, seg_scale_z * (length + 0.01)]);"
+    )?;
+    writeln!(writer, "    }}")?;
+    if surface == MazeSurface::Inner {
+        writeln!(writer, "    translate([0, 0, -0.1])")?;
+        writeln!(writer, "      cylinder(r=radius, h=height+0.2, $fn=360);")?;
+    } else if hollow {
+        writeln!(
+            writer,
+            "    cylinder(r=radius-seg_scale_x, h=height+0.1, $fn=360);"
+        )?;
     }
-    scad.push_str("  }\n");
-    scad.push_str("  \n");
-    scad.push_str("  // Base\n");
-    scad.push_str("  translate([0, 0, -height * 0.05])\n");
-    scad.push_str("    cylinder(r=radius * 1.1, h=height * 0.05, $fn=360);\n");
-    scad.push_str("}\n");
-
-    // Write the whole model
-    std::fs::write(format!("{filename}_whole.scad"), &scad)?;
+    writeln!(writer, "  }}")?;
+    // Ribs would fill the openwork grooves they cross, so openwork has none
+    if hollow && !openwork && surface == MazeSurface::Outer && support_ribs > 0 {
+        // 1mm ribs, reaching a little into the wall so they're joined to it
+        writeln!(writer, "  ")?;
+        writeln!(writer, "  // Support ribs")?;
+        writeln!(writer, "  for (i = [0 : {}])", support_ribs - 1)?;
+        writeln!(writer, "    rotate([0, 0, 360 * i / {support_ribs}])")?;
+        writeln!(writer, "      translate([0, -0.5, 0])")?;
+        writeln!(
+            writer,
+            "        cube([radius - seg_scale_x * 0.9, 1, height]);"
+        )?;
+    }
+    writeln!(writer, "  ")?;
+    writeln!(writer, "  // Base")?;
+    if surface == MazeSurface::Inner {
+        // A ring, so the ball can drop out of the bottom of the tube
+        writeln!(writer, "  difference() {{")?;
+        writeln!(writer, "    translate([0, 0, -base_height])")?;
+        writeln!(
+            writer,
+            "      cylinder(r=base_radius, h=base_height, $fn=360);"
+        )?;
+        writeln!(writer, "    translate([0, 0, -base_height - 0.1])")?;
+        writeln!(
+            writer,
+            "      cylinder(r=radius, h=base_height + 0.2, $fn=360);"
+        )?;
+        writeln!(writer, "  }}")?;
+    } else {
+        writeln!(writer, "  translate([0, 0, -base_height])")?;
+        writeln!(
+            writer,
+            "    cylinder(r=base_radius, h=base_height, $fn=360);"
+        )?;
+    }
+    writeln!(writer, "}}")?;
 
     Ok(())
 }
 
 /// Generate OpenSCAD code for the outer cylinder
 pub fn make_outer_openscad(
-    height: f64,
-    circumference: f64,
-    rows: usize,
-    cols: usize,
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
     filename: &str,
 ) -> Result<()> {
-    let radius = circumference / TAU;
-    let inner_radius = radius + 0.2;
-    let outer_radius = (radius * 1.1).max(inner_radius + 1.2);
+    let mut file = File::create(format!("{filename}.scad"))?;
+    write_outer_openscad(maze, config, &mut file)
+}
 
-    let seg_scale_x = circumference / cols as f64;
-    let seg_scale_z = height / rows as f64;
+/// Write OpenSCAD code for the outer cylinder to `writer`
+pub fn write_outer_openscad(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    writer: &mut impl Write,
+) -> Result<()> {
+    let CylinderConfig {
+        height,
+        circumference,
+        base_height_frac,
+        base_radius_frac,
+        corridor_cells,
+        vent_rows,
+        vent_cols,
+        vent_diameter,
+        ..
+    } = *config;
+    let (inner_radius, outer_radius) = config.shell_radii();
+
+    // The tooth is sized to fit the corridors of the maze
+    let (rows, cols) = maze.scaled_size(corridor_cells);
+    let seg_scale_x = circumference / cols as f64 * corridor_cells as f64;
+    let seg_scale_z = height / rows as f64 * corridor_cells as f64;
 
     let mut scad = String::new();
 
@@ -95,6 +364,10 @@ pub fn make_outer_openscad(
     scad.push_str(&format!("height = {height};\n"));
     scad.push_str(&format!("seg_scale_x = {seg_scale_x};\n"));
     scad.push_str(&format!("seg_scale_z = {seg_scale_z};\n"));
+    scad.push_str(&format!("base_height = height * {base_height_frac};\n"));
+    scad.push_str(&format!(
+        "base_radius = outer_radius * {base_radius_frac};\n"
+    ));
     scad.push('\n');
 
     scad.push_str("union() {\n");
@@ -103,22 +376,277 @@ pub fn make_outer_openscad(
     scad.push_str("  difference() {\n");
     scad.push_str("    cylinder(r=outer_radius, h=height, $fn=360);\n");
     scad.push_str("    cylinder(r=inner_radius, h=height * 1.01, $fn=360);\n");
+    if vent_rows > 0 && vent_cols > 0 {
+        // Holes spaced evenly over the shell, half a step in from the edges
+        scad.push_str("    \n");
+        scad.push_str("    // Vent holes\n");
+        scad.push_str(&format!(
+            "    for (i = [0 : {}], j = [0 : {}])\n",
+            vent_rows - 1,
+            vent_cols - 1
+        ));
+        scad.push_str(&format!(
+            "      translate([0, 0, (i + 0.5) * height / {vent_rows}])\n"
+        ));
+        scad.push_str(&format!(
+            "        rotate([0, 90, 360 * (j + 0.5) / {vent_cols}])\n"
+        ));
+        scad.push_str(&format!(
+            "          cylinder(d={vent_diameter}, h=outer_radius * 1.1, $fn=24);\n"
+        ));
+    }
     scad.push_str("  }\n");
 
     // Base
-    scad.push_str("  translate([0, 0, -height * 0.05])\n");
-    scad.push_str("    cylinder(r=outer_radius * 1.1, h=height * 0.05, $fn=360);\n");
+    scad.push_str("  translate([0, 0, -base_height])\n");
+    scad.push_str("    cylinder(r=base_radius, h=base_height, $fn=360);\n");
 
     // Tooth on outer wall at top
     scad.push_str("  // Tooth on outer wall at top\n");
     scad.push_str("  translate([- inner_radius, 0, height - seg_scale_z * 0.45])\n");
-    scad.push_str("   scale([seg_scale_x, seg_scale_x, seg_scale_z])\n");
+    // It's only as deep as a groove, however wide the corridors are
+    scad.push_str(&format!(
+        "   scale([seg_scale_x / {corridor_cells}, seg_scale_x, seg_scale_z])\n"
+    ));
     scad.push_str("    rotate([0, 90, 0])\n");
     scad.push_str("      cylinder(r1=0.45, r2=0.45 * 0.8, h=0.45, $fn=36);\n");
 
     scad.push_str("}\n");
 
-    std::fs::write(format!("{filename}.scad"), scad)?;
+    writer.write_all(scad.as_bytes())?;
 
     Ok(())
 }
+
+/// Generate OpenSCAD code for both the maze cylinder and the outer shell from the same
+/// dimensions, so the parts fit together. Writes `{basename}_maze_whole.scad` and
+/// `{basename}_shell.scad`.
+pub fn export_all(maze: &CylinderMaze, config: &CylinderConfig, basename: &str) -> Result<()> {
+    maze_to_openscad(maze, config, &format!("{basename}_maze"))?;
+    make_outer_openscad(maze, config, &format!("{basename}_shell"))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::Cell;
+
+    fn maze_scad(maze: &CylinderMaze, config: &CylinderConfig) -> String {
+        let mut scad = Vec::new();
+        write_maze_openscad(maze, config, &mut scad).unwrap();
+        String::from_utf8(scad).unwrap()
+    }
+
+    /// Total length of all the runs in the `maze_paths` array
+    fn path_run_cells(scad: &str) -> usize {
+        scad.lines()
+            .filter_map(|line| line.strip_prefix("  [")?.strip_suffix("],"))
+            .map(|run| run.split(", ").nth(2).unwrap().parse::<usize>().unwrap())
+            .sum()
+    }
+
+    fn assert_balanced(scad: &str) {
+        let mut depth = Vec::new();
+        for c in scad.chars() {
+            match c {
+                '{' | '[' | '(' => depth.push(c),
+                '}' => assert_eq!(depth.pop(), Some('{'), "Unbalanced braces"),
+                ']' => assert_eq!(depth.pop(), Some('['), "Unbalanced brackets"),
+                ')' => assert_eq!(depth.pop(), Some('('), "Unbalanced parentheses"),
+                _ => {}
+            }
+        }
+        assert!(depth.is_empty(), "Unclosed {depth:?}");
+    }
+
+    #[test]
+    fn test_maze_openscad() {
+        let mut maze = CylinderMaze::new(6, 9);
+        maze.generate_wilson();
+        let scad = maze_scad(&maze, &CylinderConfig::default());
+
+        let grid = maze.grid();
+        assert!(scad.contains(&format!("rows = {};\n", grid.len())));
+        assert!(scad.contains(&format!("cols = {};\n", grid[0].len())));
+
+        let path_cells = grid.iter().flatten().filter(|&&c| c == Cell::Path).count();
+        assert_eq!(path_run_cells(&scad), path_cells);
+
+        // Runs down a column are merged, so there are fewer of them than path cells
+        let runs = scad.lines().filter(|line| line.starts_with("  [")).count();
+        assert!(runs < path_cells);
+
+        assert_balanced(&scad);
+    }
+
+    #[test]
+    fn test_maze_openscad_corridor_cells() {
+        let mut maze = CylinderMaze::new(4, 5);
+        maze.generate_wilson();
+        let config = CylinderConfig {
+            corridor_cells: 3,
+            ..CylinderConfig::default()
+        };
+        let scad = maze_scad(&maze, &config);
+
+        let grid = maze.scaled_grid(3);
+        assert!(scad.contains(&format!("rows = {};\n", grid.len())));
+        assert!(scad.contains(&format!("cols = {};\n", grid[0].len())));
+
+        let path_cells = grid.iter().flatten().filter(|&&c| c == Cell::Path).count();
+        assert_eq!(path_run_cells(&scad), path_cells);
+    }
+
+    #[test]
+    fn test_maze_openscad_inner_surface() {
+        let mut maze = CylinderMaze::new(4, 5);
+        maze.generate_wilson();
+        let config = CylinderConfig {
+            surface: MazeSurface::Inner,
+            ..CylinderConfig::default()
+        };
+        let scad = maze_scad(&maze, &config);
+
+        assert!(scad.contains("cylinder(r=(radius + seg_scale_x), h=height, $fn=360);"));
+        assert!(scad.contains("translate([radius - seg_scale_x * 0.56,"));
+        assert!(scad.contains("cylinder(r=radius, h=height+0.2, $fn=360);"));
+        assert_balanced(&scad);
+
+        let outer = maze_scad(&maze, &CylinderConfig::default());
+        assert!(outer.contains("cylinder(r=radius, h=height, $fn=360);"));
+    }
+
+    #[test]
+    fn test_maze_openscad_support_ribs() {
+        let mut maze = CylinderMaze::new(4, 5);
+        maze.generate_wilson();
+        let config = CylinderConfig {
+            hollow: true,
+            support_ribs: 3,
+            ..CylinderConfig::default()
+        };
+        let scad = maze_scad(&maze, &config);
+        assert!(scad.contains("for (i = [0 : 2])"));
+        assert!(scad.contains("rotate([0, 0, 360 * i / 3])"));
+        assert_balanced(&scad);
+
+        // Only a hollow maze has room for them
+        let config = CylinderConfig {
+            hollow: false,
+            ..config
+        };
+        assert!(!maze_scad(&maze, &config).contains("Support ribs"));
+    }
+
+    #[test]
+    fn test_cell_to_cylindrical() {
+        let maze = CylinderMaze::new(4, 5);
+        let config = CylinderConfig::default();
+        for (row, col) in [(0, 0), (1, 4), (3, 2)] {
+            let (angle, height) = cell_to_cylindrical(&maze, &config, row, col);
+            let [x, y, z] = cell_center(&maze, &config, row, col);
+            assert!((y.atan2(x).rem_euclid(TAU) - angle).abs() < 1e-9);
+            assert_eq!(z, height);
+        }
+
+        // The last column is just short of all the way round, before the seam
+        let (angle, _) = cell_to_cylindrical(&maze, &config, 0, 4);
+        assert!((angle - TAU * 9.0 / 11.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_maze_openscad_openwork() {
+        let mut maze = CylinderMaze::new(4, 5);
+        maze.generate_wilson();
+        let config = CylinderConfig {
+            openwork: true,
+            support_ribs: 4,
+            ..CylinderConfig::default()
+        };
+        let scad = maze_scad(&maze, &config);
+        assert!(!scad.contains("Support ribs"));
+
+        // Cut from inside the bore to outside the cylinder
+        assert!(scad.contains("translate([radius - seg_scale_x * 1.1,"));
+        assert!(scad.contains("cube([seg_scale_x * 1.2,"));
+        assert!(scad.contains("cylinder(r=radius-seg_scale_x, h=height+0.1, $fn=360);"));
+        assert_balanced(&scad);
+    }
+
+    #[test]
+    fn test_cell_center() {
+        let maze = CylinderMaze::new(4, 5);
+        let config = CylinderConfig {
+            height: 90.0,
+            circumference: 110.0,
+            ..CylinderConfig::default()
+        };
+
+        // The grid is 9 squares high and 11 around, each 10mm square
+        let [x, y, z] = cell_center(&maze, &config, 0, 0);
+        let angle = TAU / 11.0;
+        let radius = 110.0 / TAU - 10.0 * 0.225;
+        assert!((x - radius * angle.cos()).abs() < 1e-9);
+        assert!((y - radius * angle.sin()).abs() < 1e-9);
+        assert!((z - 15.0).abs() < 1e-9);
+
+        let [_, _, z] = cell_center(&maze, &config, 3, 4);
+        assert!((z - 75.0).abs() < 1e-9);
+
+        // Wider corridors move the middle of the cell along
+        let config = CylinderConfig {
+            corridor_cells: 2,
+            ..config
+        };
+        let [x, y, _] = cell_center(&maze, &config, 0, 1);
+        assert!((y.atan2(x) - TAU * 4.5 / 16.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_outer_openscad() {
+        let maze = CylinderMaze::new(6, 9);
+        let mut scad = Vec::new();
+        write_outer_openscad(&maze, &CylinderConfig::default(), &mut scad).unwrap();
+        let scad = String::from_utf8(scad).unwrap();
+
+        assert!(scad.contains("height = 60;\n"));
+        assert_balanced(&scad);
+    }
+
+    #[test]
+    fn test_outer_openscad_vents() {
+        let maze = CylinderMaze::new(6, 9);
+        let config = CylinderConfig {
+            vent_rows: 3,
+            vent_cols: 8,
+            ..CylinderConfig::default()
+        };
+        let mut scad = Vec::new();
+        write_outer_openscad(&maze, &config, &mut scad).unwrap();
+        let scad = String::from_utf8(scad).unwrap();
+
+        assert!(scad.contains("for (i = [0 : 2], j = [0 : 7])"));
+        assert_balanced(&scad);
+
+        let mut scad = Vec::new();
+        write_outer_openscad(&maze, &CylinderConfig::default(), &mut scad).unwrap();
+        assert!(!String::from_utf8(scad).unwrap().contains("Vent holes"));
+    }
+
+    #[test]
+    fn test_outer_openscad_clearance() {
+        let maze = CylinderMaze::new(6, 9);
+        let config = CylinderConfig {
+            circumference: TAU * 20.0,
+            clearance: 0.5,
+            ..CylinderConfig::default()
+        };
+        let mut scad = Vec::new();
+        write_outer_openscad(&maze, &config, &mut scad).unwrap();
+        let scad = String::from_utf8(scad).unwrap();
+
+        assert!(scad.contains("inner_radius = 20.5;\n"));
+    }
+}
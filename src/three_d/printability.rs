@@ -0,0 +1,95 @@
+use super::CylinderConfig;
+use crate::maze::CylinderMaze;
+
+/// Physical sizes of the smallest features of the printed maze and shell, in mm
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintabilityReport {
+    /// Width of a corridor around the cylinder
+    pub corridor_width: f64,
+    /// Height of a corridor up the cylinder
+    pub corridor_height: f64,
+    /// Thickness of the thinnest wall between corridors
+    pub wall_thickness: f64,
+    /// How deep the corridors are cut into the cylinder
+    pub groove_depth: f64,
+    /// Thickness of the outer shell
+    pub shell_thickness: f64,
+    /// A description of each feature that is thinner than the nozzle
+    pub problems: Vec<String>,
+}
+
+impl PrintabilityReport {
+    /// Whether every feature is at least as wide as the nozzle
+    pub fn is_printable(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Work out how big the features of the maze will be when printed at `config`'s size,
+/// and flag any that are smaller than a `nozzle_mm` nozzle can print. The sizes match the
+/// OpenSCAD export: each grid square is a wall or part of a corridor.
+pub fn printability_report(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    nozzle_mm: f64,
+) -> PrintabilityReport {
+    let (rows, cols) = maze.scaled_size(config.corridor_cells);
+    let square_width = config.circumference / cols as f64;
+    let square_height = config.height / rows as f64;
+    let (inner_radius, outer_radius) = config.shell_radii();
+
+    let corridor_cells = config.corridor_cells as f64;
+    let features = [
+        ("Corridor width", square_width * corridor_cells),
+        ("Corridor height", square_height * corridor_cells),
+        ("Wall thickness", square_width.min(square_height)),
+        ("Groove depth", square_width * 0.45),
+        ("Shell thickness", outer_radius - inner_radius),
+    ];
+    let problems = features
+        .iter()
+        .filter(|&&(_, size)| size < nozzle_mm)
+        .map(|(name, size)| format!("{name} of {size:.3}mm is less than the {nozzle_mm}mm nozzle"))
+        .collect();
+
+    let [
+        corridor_width,
+        corridor_height,
+        wall_thickness,
+        groove_depth,
+        shell_thickness,
+    ] = features.map(|(_, size)| size);
+    PrintabilityReport {
+        corridor_width,
+        corridor_height,
+        wall_thickness,
+        groove_depth,
+        shell_thickness,
+        problems,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_printability_report() {
+        let maze = CylinderMaze::new(10, 20);
+        let config = CylinderConfig::default();
+
+        let report = printability_report(&maze, &config, 0.4);
+        assert!(report.is_printable(), "{:?}", report.problems);
+        assert!((report.wall_thickness - 100.0 / 41.0).abs() < 1e-9);
+
+        // A tiny cylinder has walls thinner than the nozzle
+        let config = CylinderConfig {
+            height: 10.0,
+            circumference: 10.0,
+            ..config
+        };
+        let report = printability_report(&maze, &config, 0.4);
+        assert!(!report.is_printable());
+        assert!(report.problems[0].starts_with("Corridor width"));
+    }
+}
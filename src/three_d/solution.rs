@@ -0,0 +1,102 @@
+use super::CylinderConfig;
+use super::openscad::grid_square_center;
+use crate::maze::CylinderMaze;
+use anyhow::{Result, bail};
+use std::fs::File;
+use std::io::Write;
+
+/// Generate OpenSCAD code for the solution alone, as a tube following the middle of the
+/// corridors from `start` to `end`, to sit in the maze cylinder made with the same config
+pub fn solution_to_openscad(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    start: (usize, usize),
+    end: (usize, usize),
+    tube_radius: f64,
+    filename: &str,
+) -> Result<()> {
+    let mut file = File::create(format!("{filename}.scad"))?;
+    write_solution_openscad(maze, config, start, end, tube_radius, &mut file)
+}
+
+/// Write OpenSCAD code for the solution tube to `writer`
+pub fn write_solution_openscad(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    start: (usize, usize),
+    end: (usize, usize),
+    tube_radius: f64,
+    writer: &mut impl Write,
+) -> Result<()> {
+    let Some(squares) = maze.solution_squares(start, end) else {
+        bail!("No solution from {start:?} to {end:?}");
+    };
+
+    let mut scad = String::new();
+    scad.push_str(&format!("tube_radius = {tube_radius};\n"));
+    scad.push('\n');
+
+    // The middle of every square along the route
+    scad.push_str("// Solution: [x, y, z] at the middle of each square along the route\n");
+    scad.push_str("solution = [\n");
+    for (row, col) in squares {
+        let [x, y, z] = grid_square_center(maze, config, row, col);
+        scad.push_str(&format!("  [{x}, {y}, {z}],\n"));
+    }
+    scad.push_str("];\n\n");
+
+    // Join each point to the next with a rounded segment
+    scad.push_str("for (i = [0 : len(solution) - 2]) {\n");
+    scad.push_str("  hull() {\n");
+    scad.push_str("    translate(solution[i]) sphere(r=tube_radius, $fn=16);\n");
+    scad.push_str("    translate(solution[i + 1]) sphere(r=tube_radius, $fn=16);\n");
+    scad.push_str("  }\n");
+    scad.push_str("}\n");
+
+    writer.write_all(scad.as_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solution_openscad() {
+        let mut maze = CylinderMaze::new(5, 6);
+        let (start, end) = maze.generate_wilson();
+        let config = CylinderConfig::default();
+        let mut scad = Vec::new();
+        write_solution_openscad(&maze, &config, start, end, 1.0, &mut scad).unwrap();
+        let scad = String::from_utf8(scad).unwrap();
+
+        let squares = maze.solution_squares(start, end).unwrap();
+        let points: Vec<&str> = scad
+            .lines()
+            .filter(|line| line.starts_with("  ["))
+            .collect();
+        assert_eq!(points.len(), squares.len());
+
+        // Every point is inside the cylinder, at the depth of the grooves
+        let radius = config.circumference / std::f64::consts::TAU;
+        for point in points {
+            let coords: Vec<f64> = point
+                .trim_matches(|c| " [],".contains(c))
+                .split(", ")
+                .map(|n| n.parse().unwrap())
+                .collect();
+            let r = coords[0].hypot(coords[1]);
+            assert!(
+                r < radius && r > radius - 2.0,
+                "Point {coords:?} off the groove"
+            );
+            assert!((0.0..config.height).contains(&coords[2]));
+        }
+
+        let unsolvable = CylinderMaze::new(2, 2);
+        let mut scad = Vec::new();
+        let result = write_solution_openscad(&unsolvable, &config, (0, 0), (1, 1), 1.0, &mut scad);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,37 @@
+use crate::maze::{Cell, GridSnapshot};
+use anyhow::{Result, bail};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, Rgba, RgbaImage};
+use std::fs::File;
+
+/// Size of each grid square in the animation, in pixels
+const SCALE: u32 = 8;
+
+/// How long each frame is shown, in milliseconds
+const FRAME_DELAY_MS: u32 = 40;
+
+/// Render the snapshots of a maze being generated as an animated GIF
+pub fn export_gif(snapshots: &[GridSnapshot], filename: &str) -> Result<()> {
+    let Some(first) = snapshots.first() else {
+        bail!("No snapshots to animate");
+    };
+    let width = first[0].len() as u32 * SCALE;
+    let height = first.len() as u32 * SCALE;
+
+    let mut encoder = GifEncoder::new(File::create(filename)?);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    for grid in snapshots {
+        let image = RgbaImage::from_fn(width, height, |x, y| {
+            match grid[(y / SCALE) as usize][(x / SCALE) as usize] {
+                Cell::Wall => Rgba([0, 0, 0, 255]),
+                Cell::Path => Rgba([255, 255, 255, 255]),
+                Cell::Solution => Rgba([255, 0, 0, 255]),
+            }
+        });
+        let delay = Delay::from_numer_denom_ms(FRAME_DELAY_MS, 1);
+        encoder.encode_frame(Frame::from_parts(image, 0, 0, delay))?;
+    }
+
+    Ok(())
+}
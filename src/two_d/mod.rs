@@ -0,0 +1,7 @@
+#[cfg(feature = "image")]
+mod gif;
+mod svg;
+
+#[cfg(feature = "image")]
+pub use gif::export_gif;
+pub use svg::{export_flat_svg, export_wrap_svg, write_flat_svg, write_wrap_svg};
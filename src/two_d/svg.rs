@@ -0,0 +1,183 @@
+use crate::maze::{Cell, CylinderMaze};
+use crate::three_d::{CylinderConfig, grid_square_to_cylindrical};
+use anyhow::Result;
+use std::f64::consts::TAU;
+use std::fs::File;
+use std::io::Write;
+
+/// Generate an SVG of the maze unrolled into a flat panel, for laser cutting. Each cell
+/// is `cell_size` mm square. The left and right edges join when the panel is bent into a
+/// cylinder, and are marked with a dashed line.
+pub fn export_flat_svg(maze: &CylinderMaze, cell_size: f64, filename: &str) -> Result<()> {
+    let mut file = File::create(format!("{filename}.svg"))?;
+    write_flat_svg(maze, cell_size, &mut file)
+}
+
+/// Write an SVG of the maze unrolled into a flat panel to `writer`
+pub fn write_flat_svg(maze: &CylinderMaze, cell_size: f64, writer: &mut impl Write) -> Result<()> {
+    let size = (
+        maze.cols() as f64 * cell_size,
+        maze.rows() as f64 * cell_size,
+    );
+    write_svg(maze, size, writer, |grid_row, grid_col| {
+        (
+            (grid_col / 2) as f64 * cell_size,
+            (grid_row / 2) as f64 * cell_size,
+        )
+    })
+}
+
+/// Generate an SVG of the maze surface at the size of the cylinder made with `config`, to
+/// print and wrap around it. The width is the circumference, and the height is the
+/// height of the cylinder, so the ends meet at the seam. Every wall is where it is on the
+/// model, with row 0 at the bottom.
+pub fn export_wrap_svg(maze: &CylinderMaze, config: &CylinderConfig, filename: &str) -> Result<()> {
+    let mut file = File::create(format!("{filename}.svg"))?;
+    write_wrap_svg(maze, config, &mut file)
+}
+
+/// Write an SVG of the maze surface at the size of the cylinder made with `config` to
+/// `writer`
+pub fn write_wrap_svg(
+    maze: &CylinderMaze,
+    config: &CylinderConfig,
+    writer: &mut impl Write,
+) -> Result<()> {
+    let size = (config.circumference, config.height);
+    write_svg(maze, size, writer, |grid_row, grid_col| {
+        let (angle, height) = grid_square_to_cylindrical(maze, config, grid_row, grid_col);
+        (angle / TAU * config.circumference, config.height - height)
+    })
+}
+
+/// Write an SVG of the maze, `size` mm wide and tall, with each wall drawn as a line
+/// between the posts at its ends. `position` gives the x and y of a post from its
+/// square in the grid.
+fn write_svg(
+    maze: &CylinderMaze,
+    (width, height): (f64, f64),
+    writer: &mut impl Write,
+    position: impl Fn(usize, usize) -> (f64, f64),
+) -> Result<()> {
+    let grid = maze.grid();
+    debug_assert!(
+        maze.has_valid_shape(),
+        "Grid doesn't match the {}x{} maze",
+        maze.rows(),
+        maze.cols()
+    );
+    let rows = maze.rows();
+    let cols = maze.cols();
+
+    let mut svg = String::new();
+    svg.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}mm\" height=\"{height}mm\" viewBox=\"0 0 {width} {height}\">\n"
+    ));
+
+    // Seam, where the left and right edges join
+    svg.push_str("  <g stroke=\"blue\" stroke-width=\"0.1\" stroke-dasharray=\"1,1\">\n");
+    svg.push_str(&format!(
+        "    <line x1=\"0\" y1=\"0\" x2=\"0\" y2=\"{height}\"/>\n"
+    ));
+    svg.push_str(&format!(
+        "    <line x1=\"{width}\" y1=\"0\" x2=\"{width}\" y2=\"{height}\"/>\n"
+    ));
+    svg.push_str("  </g>\n");
+
+    svg.push_str("  <g stroke=\"black\" stroke-width=\"0.1\" stroke-linecap=\"square\">\n");
+
+    // Walls above each cell, including the bottom edge below the last row
+    for row in 0..=rows {
+        for col in 0..cols {
+            if grid[2 * row][2 * col + 1] == Cell::Wall {
+                let from = position(2 * row, 2 * col);
+                let to = position(2 * row, 2 * col + 2);
+                push_line(&mut svg, from, to);
+            }
+        }
+    }
+
+    // Walls to the left of each cell. The seam wall is drawn on both edges, since they
+    // are the same wall once the panel is bent round.
+    for row in 0..rows {
+        for col in 0..=cols {
+            if grid[2 * row + 1][2 * col] == Cell::Wall {
+                let from = position(2 * row, 2 * col);
+                let to = position(2 * row + 2, 2 * col);
+                push_line(&mut svg, from, to);
+            }
+        }
+    }
+
+    svg.push_str("  </g>\n");
+    svg.push_str("</svg>\n");
+
+    writer.write_all(svg.as_bytes())?;
+
+    Ok(())
+}
+
+/// Add a line between two points, in mm
+fn push_line(svg: &mut String, from: (f64, f64), to: (f64, f64)) {
+    svg.push_str(&format!(
+        "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
+        from.0, from.1, to.0, to.1
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::three_d::cell_to_cylindrical;
+
+    #[test]
+    fn test_write_flat_svg() {
+        // Every wall of an ungenerated 2x3 maze is closed: three rows of three horizontal
+        // walls, and two rows of four vertical walls, plus the two seam markers
+        let maze = CylinderMaze::new(2, 3);
+        let mut svg = Vec::new();
+        write_flat_svg(&maze, 5.0, &mut svg).unwrap();
+
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("width=\"15mm\" height=\"10mm\""));
+        assert_eq!(svg.matches("<line").count(), 3 * 3 + 2 * 4 + 2);
+    }
+
+    #[test]
+    fn test_write_wrap_svg() {
+        let maze = CylinderMaze::new(2, 3);
+        let config = CylinderConfig {
+            circumference: 90.0,
+            height: 40.0,
+            ..CylinderConfig::default()
+        };
+        let mut svg = Vec::new();
+        write_wrap_svg(&maze, &config, &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("width=\"90mm\" height=\"40mm\""));
+
+        // The wall between cells (0, 1) and (1, 1) runs across the middle of column 1, half
+        // way between the rows, with row 0 at the bottom
+        let (angle, bottom) = cell_to_cylindrical(&maze, &config, 0, 1);
+        let (_, top) = cell_to_cylindrical(&maze, &config, 1, 1);
+        let x = angle / TAU * 90.0;
+        let y = 40.0 - (bottom + top) / 2.0;
+        let lines: Vec<[f64; 4]> = svg
+            .lines()
+            .filter(|line| line.trim_start().starts_with("<line"))
+            .filter_map(|line| {
+                let values: Vec<f64> = line
+                    .split('"')
+                    .skip(1)
+                    .step_by(2)
+                    .map(|value| value.parse().unwrap())
+                    .collect();
+                values.try_into().ok()
+            })
+            .collect();
+        assert!(lines.iter().any(|&[x1, y1, x2, y2]| {
+            (y1 - y).abs() < 1e-9 && (y2 - y).abs() < 1e-9 && ((x1 + x2) / 2.0 - x).abs() < 1e-9
+        }));
+    }
+}